@@ -0,0 +1,103 @@
+#![feature(test)]
+
+extern crate test;
+
+use legion::*;
+use legion_transform::prelude::*;
+use test::Bencher;
+
+fn bench_propagation(b: &mut Bencher, world: &mut World, dirty: bool) {
+    let mut resources = Resources::default();
+
+    // Run the whole bundle once to build the `Children` of every parent.
+    let mut transform_system_bundle = transform_system_bundle::build();
+    for system in transform_system_bundle.iter_mut() {
+        system.prepare(world);
+        system.run(world, &mut resources);
+        system.command_buffer_mut(world.id()).unwrap().flush(world);
+    }
+
+    // Then time just the propagation, either of an unchanged world (which should be close to
+    // free) or after moving every root.
+    let mut schedule = Schedule::builder()
+        .add_system(local_to_world_propagate_system::build())
+        .build();
+    let mut roots = <Write<LocalToWorld>>::query().filter(!component::<Parent>());
+    b.iter(|| {
+        if dirty {
+            roots.for_each_mut(world, |local_to_world| local_to_world.0[(0, 3)] += 1.0);
+        }
+        schedule.execute(world, &mut resources);
+    });
+}
+
+fn wide(world: &mut World) {
+    for _ in 0..1000 {
+        let root = world.push((LocalToWorld::identity(), Translation::new(1.0, 2.0, 3.0)));
+        world.extend(vec![
+            (
+                LocalToWorld::identity(),
+                LocalToParent::identity(),
+                Translation::new(1.0, 0.0, 0.0),
+                Parent(root),
+            );
+            10
+        ]);
+    }
+}
+
+fn deep(world: &mut World) {
+    for _ in 0..10 {
+        let mut parent = world.push((LocalToWorld::identity(), Translation::new(1.0, 2.0, 3.0)));
+        for _ in 0..1000 {
+            parent = world.push((
+                LocalToWorld::identity(),
+                LocalToParent::identity(),
+                Translation::new(1.0, 0.0, 0.0),
+                Parent(parent),
+            ));
+        }
+    }
+}
+
+/// Many independent hierarchies, each a root with a handful of direct children, none of which
+/// move.
+#[bench]
+fn local_to_world_propagate_wide_static(b: &mut Bencher) {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut world = World::default();
+    wide(&mut world);
+    bench_propagation(b, &mut world, false);
+}
+
+/// Many independent hierarchies, each a root with a handful of direct children, with every root
+/// moving.
+#[bench]
+fn local_to_world_propagate_wide_dirty(b: &mut Bencher) {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut world = World::default();
+    wide(&mut world);
+    bench_propagation(b, &mut world, true);
+}
+
+/// A few hierarchies made of long chains of single children, none of which move.
+#[bench]
+fn local_to_world_propagate_deep_static(b: &mut Bencher) {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut world = World::default();
+    deep(&mut world);
+    bench_propagation(b, &mut world, false);
+}
+
+/// A few hierarchies made of long chains of single children, with every root moving.
+#[bench]
+fn local_to_world_propagate_deep_dirty(b: &mut Bencher) {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut world = World::default();
+    deep(&mut world);
+    bench_propagation(b, &mut world, true);
+}
@@ -0,0 +1,249 @@
+use crate::{
+    components::*,
+    decompose::decompose,
+    ecs::{systems::ParallelRunnable, *},
+    hierarchy,
+    math::{Matrix4, Translation3, UnitQuaternion, Vector3},
+    Float,
+};
+use std::collections::HashMap;
+
+/// Replaces the rotation part of the `LocalToWorld` of entities with a `Billboard`, so that they
+/// face their camera, keeping the translation and scale derived from their parents. Part of the
+/// transform system bundle, after propagation (and constraints).
+///
+/// The descendants of a billboard have their `LocalToWorld` re-derived from the billboarded one,
+/// so children always turn along with it. Billboards nested under other billboards are handled
+/// outermost first.
+pub fn build() -> impl ParallelRunnable {
+    SystemBuilder::<()>::new("BillboardSystem")
+        .with_query(<(Entity, Read<Billboard>)>::query().filter(component::<LocalToWorld>()))
+        .read_component::<Children>()
+        .read_component::<Parent>()
+        .read_component::<LocalToParent>()
+        .write_component::<LocalToWorld>()
+        .build(move |_commands, world, _resource, query| {
+            let mut billboards = query
+                .iter(world)
+                .map(|(entity, billboard)| (*entity, *billboard))
+                .collect::<Vec<_>>();
+            if billboards.is_empty() {
+                return;
+            }
+            // A stable sort, so billboards at the same depth stay in query order.
+            billboards.sort_by_key(|(entity, _)| hierarchy::ancestors(world, *entity).count());
+
+            // The `LocalToWorld`s computed so far, which later billboards must see.
+            let mut computed = HashMap::<Entity, Matrix4<Float>>::new();
+            let mut order = Vec::new();
+            let local_to_world = |computed: &HashMap<Entity, Matrix4<Float>>, entity: Entity| {
+                computed.get(&entity).cloned().or_else(|| {
+                    world
+                        .entry_ref(entity)
+                        .and_then(|entry| entry.into_component::<LocalToWorld>().ok())
+                        .map(|local_to_world| local_to_world.0)
+                })
+            };
+
+            for (entity, billboard) in billboards {
+                let camera = match local_to_world(&computed, billboard.camera) {
+                    Some(camera) => camera,
+                    None => {
+                        log::warn!(
+                            "The Billboard camera {:?} of {:?} has no LocalToWorld",
+                            billboard.camera,
+                            entity
+                        );
+                        continue;
+                    }
+                };
+                let current = match local_to_world(&computed, entity) {
+                    Some(current) => current,
+                    None => continue,
+                };
+                let billboarded = match face_camera(&current, &camera, billboard.mode) {
+                    Some(billboarded) => billboarded,
+                    None => continue,
+                };
+
+                if computed.insert(entity, billboarded).is_none() {
+                    order.push(entity);
+                }
+                for (descendant, _) in hierarchy::descendants_depth_first(world, entity) {
+                    // Parents come before their children, so the parent's is always computed.
+                    let parent_local_to_world = hierarchy::parent(world, descendant)
+                        .and_then(|parent| computed.get(&parent).cloned());
+                    let local_to_parent = world
+                        .entry_ref(descendant)
+                        .and_then(|entry| entry.into_component::<LocalToParent>().ok())
+                        .map(|local_to_parent| local_to_parent.0);
+                    if let (Some(parent_local_to_world), Some(local_to_parent)) =
+                        (parent_local_to_world, local_to_parent)
+                    {
+                        if computed
+                            .insert(descendant, parent_local_to_world * local_to_parent)
+                            .is_none()
+                        {
+                            order.push(descendant);
+                        }
+                    }
+                }
+            }
+
+            for entity in order {
+                if let Some(target) = world
+                    .entry_mut(entity)
+                    .and_then(|entry| entry.into_component_mut::<LocalToWorld>().ok())
+                {
+                    *target = LocalToWorld(computed[&entity]);
+                }
+            }
+        })
+}
+
+/// `local_to_world` with its rotation replaced so that it faces `camera`.
+fn face_camera(
+    local_to_world: &Matrix4<Float>,
+    camera: &Matrix4<Float>,
+    mode: BillboardMode,
+) -> Option<Matrix4<Float>> {
+    let (translation, _, scale) = decompose(local_to_world);
+    let to_camera = Vector3::new(camera[(0, 3)], camera[(1, 3)], camera[(2, 3)]) - translation;
+
+    let rotation = match mode {
+        BillboardMode::Spherical => {
+            let up = Vector3::new(camera[(0, 1)], camera[(1, 1)], camera[(2, 1)]);
+            face(&to_camera, &up)?
+        }
+        BillboardMode::Cylindrical(axis) => {
+            let axis = axis.try_normalize(1.0e-6)?;
+            // Only the part of the direction around the axis matters.
+            face(&(to_camera - axis * axis.dot(&to_camera)), &axis)?
+        }
+    };
+
+    Some(
+        Translation3::from(translation).to_homogeneous()
+            * rotation.to_homogeneous()
+            * Matrix4::new_nonuniform_scaling(&scale),
+    )
+}
+
+/// The rotation pointing `+Z` along `direction` with `+Y` towards `up`, if they are not parallel.
+fn face(direction: &Vector3<Float>, up: &Vector3<Float>) -> Option<UnitQuaternion<Float>> {
+    if direction.norm() < 1.0e-6 || direction.cross(up).norm() < 1.0e-6 * direction.norm() {
+        return None;
+    }
+    Some(UnitQuaternion::face_towards(direction, up))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        local_to_parent_system, local_to_world_propagate_system, local_to_world_system,
+        missing_previous_parent_system, parent_update_system,
+    };
+
+    #[test]
+    fn faces_camera() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+        let mut schedule = Schedule::builder().add_system(build()).build();
+
+        // The `LocalToWorld`s are set as if propagation had already run.
+        let camera = world.push((LocalToWorld(Matrix4::new_translation(&Vector3::new(
+            10.0, 10.0, 0.0,
+        ))),));
+        let local_to_world = Matrix4::new_nonuniform_scaling(&Vector3::new(1.0, 2.0, 3.0));
+        let spherical = world.push((LocalToWorld(local_to_world), Billboard::spherical(camera)));
+        let cylindrical = world.push((
+            LocalToWorld(local_to_world),
+            Billboard::cylindrical(camera, Vector3::y()),
+        ));
+
+        schedule.execute(&mut world, &mut resources);
+
+        let get = |entity| {
+            world
+                .entry_ref(entity)
+                .unwrap()
+                .get_component::<LocalToWorld>()
+                .unwrap()
+                .0
+        };
+        let (translation, rotation, scale) = decompose(&get(spherical));
+        assert!(translation.norm() < 1e-5);
+        assert!((scale - Vector3::new(1.0, 2.0, 3.0)).norm() < 1e-5);
+        assert!((rotation * Vector3::z() - Vector3::new(1.0, 1.0, 0.0).normalize()).norm() < 1e-5);
+
+        // Stays upright, turning only around the Y axis.
+        let (_, rotation, scale) = decompose(&get(cylindrical));
+        assert!((scale - Vector3::new(1.0, 2.0, 3.0)).norm() < 1e-5);
+        assert!((rotation * Vector3::y() - Vector3::y()).norm() < 1e-5);
+        assert!((rotation * Vector3::z() - Vector3::x()).norm() < 1e-5);
+    }
+
+    #[test]
+    fn children_follow_billboard() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+        let mut schedule = Schedule::builder()
+            .add_system(missing_previous_parent_system::build())
+            .flush()
+            .add_system(parent_update_system::build())
+            .flush()
+            .add_system(local_to_parent_system::build())
+            .add_system(local_to_world_system::build())
+            .add_system(local_to_world_propagate_system::build())
+            .add_system(build())
+            .build();
+
+        let camera = world.push((LocalToWorld(Matrix4::new_translation(&Vector3::new(
+            10.0, 0.0, 0.0,
+        ))),));
+        let billboard = world.push((
+            Translation::identity(),
+            LocalToWorld::identity(),
+            Billboard::cylindrical(camera, Vector3::y()),
+        ));
+        // In front of the billboard, so it ends up between the billboard and the camera.
+        let child = world.push((
+            Translation::new(0.0, 0.0, 1.0),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            Parent(billboard),
+        ));
+
+        let child_translation = |world: &World| {
+            decompose(
+                &world
+                    .entry_ref(child)
+                    .unwrap()
+                    .get_component::<LocalToWorld>()
+                    .unwrap()
+                    .0,
+            )
+            .0
+        };
+
+        schedule.execute(&mut world, &mut resources);
+        assert!((child_translation(&world) - Vector3::new(1.0, 0.0, 0.0)).norm() < 1e-5);
+
+        // Whether or not the billboard itself moved, the child always follows the billboarded
+        // `LocalToWorld`.
+        *world
+            .entry_mut(billboard)
+            .unwrap()
+            .get_component_mut::<Translation>()
+            .unwrap() = Translation::new(0.0, 1.0, 0.0);
+        schedule.execute(&mut world, &mut resources);
+        assert!((child_translation(&world) - Vector3::new(1.0, 1.0, 0.0)).norm() < 1e-5);
+        schedule.execute(&mut world, &mut resources);
+        assert!((child_translation(&world) - Vector3::new(1.0, 1.0, 0.0)).norm() < 1e-5);
+    }
+}
@@ -0,0 +1,35 @@
+use crate::{ecs::*, math::Vector3, Float};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum BillboardMode {
+    /// Turns freely so that `+Z` points at the camera, keeping `+Y` as close as possible to the
+    /// camera's up.
+    Spherical,
+    /// Only turns around the given world-space axis, which `+Y` is aligned with, so that `+Z`
+    /// points at the camera as closely as possible (eg. for trees and nameplates).
+    Cylindrical(Vector3<Float>),
+}
+
+/// Makes an entity face a camera entity, see the `billboard_system`.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Billboard {
+    pub camera: Entity,
+    pub mode: BillboardMode,
+}
+
+impl Billboard {
+    pub fn spherical(camera: Entity) -> Self {
+        Self {
+            camera,
+            mode: BillboardMode::Spherical,
+        }
+    }
+
+    pub fn cylindrical(camera: Entity, axis: Vector3<Float>) -> Self {
+        Self {
+            camera,
+            mode: BillboardMode::Cylindrical(axis),
+        }
+    }
+}
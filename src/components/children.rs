@@ -1,8 +1,9 @@
 use crate::ecs::*;
+use serde::{Deserialize, Serialize};
 use shrinkwraprs::Shrinkwrap;
 use smallvec::SmallVec;
 
-#[derive(Shrinkwrap, Default, Clone)]
+#[derive(Shrinkwrap, Default, Clone, Serialize, Deserialize)]
 #[shrinkwrap(mutable)]
 pub struct Children(pub SmallVec<[Entity; 8]>);
 
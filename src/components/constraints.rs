@@ -0,0 +1,100 @@
+use crate::{
+    ecs::*,
+    math::{Matrix4, UnitQuaternion, Vector3},
+    Float,
+};
+use serde::{Deserialize, Serialize};
+
+/// An entity followed by a constraint, and how much it counts relative to the other sources.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct ConstraintSource {
+    pub entity: Entity,
+    pub weight: Float,
+}
+
+impl ConstraintSource {
+    pub fn new(entity: Entity, weight: Float) -> Self {
+        Self { entity, weight }
+    }
+}
+
+/// Moves an entity to the weighted average of the world-space positions of its sources, plus
+/// `offset`. See the `constraint_system`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct PositionConstraint {
+    pub sources: Vec<ConstraintSource>,
+    /// A world-space offset added to the averaged position.
+    pub offset: Vector3<Float>,
+    /// How much the constraint applies, from 0 (not at all) to 1 (fully).
+    pub weight: Float,
+}
+
+impl PositionConstraint {
+    pub fn new(sources: Vec<ConstraintSource>) -> Self {
+        Self {
+            sources,
+            offset: Vector3::zeros(),
+            weight: 1.0,
+        }
+    }
+}
+
+/// Turns an entity to the weighted blend of the world-space rotations of its sources, followed
+/// by `offset`. See the `constraint_system`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct RotationConstraint {
+    pub sources: Vec<ConstraintSource>,
+    /// A rotation applied in the space of the blended rotation.
+    pub offset: UnitQuaternion<Float>,
+    /// How much the constraint applies, from 0 (not at all) to 1 (fully).
+    pub weight: Float,
+}
+
+impl RotationConstraint {
+    pub fn new(sources: Vec<ConstraintSource>) -> Self {
+        Self {
+            sources,
+            offset: UnitQuaternion::identity(),
+            weight: 1.0,
+        }
+    }
+}
+
+/// Scales an entity to the weighted average of the world-space scales of its sources. See the
+/// `constraint_system`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ScaleConstraint {
+    pub sources: Vec<ConstraintSource>,
+    /// How much the constraint applies, from 0 (not at all) to 1 (fully).
+    pub weight: Float,
+}
+
+impl ScaleConstraint {
+    pub fn new(sources: Vec<ConstraintSource>) -> Self {
+        Self {
+            sources,
+            weight: 1.0,
+        }
+    }
+}
+
+/// Makes an entity follow its sources as if it were their child, without being in their
+/// `Children`. See the `constraint_system`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ParentConstraint {
+    pub sources: Vec<ConstraintSource>,
+    /// The transform of the entity relative to each source, like a `LocalToParent`.
+    pub offset: Matrix4<Float>,
+    /// How much the constraint applies, from 0 (not at all) to 1 (fully).
+    pub weight: Float,
+}
+
+impl ParentConstraint {
+    pub fn new(sources: Vec<ConstraintSource>) -> Self {
+        Self {
+            sources,
+            offset: Matrix4::identity(),
+            weight: 1.0,
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use crate::{math::Matrix4, Float};
+use serde::{Deserialize, Serialize};
+use shrinkwraprs::Shrinkwrap;
+use std::fmt;
+
+/// The world transform to render with when simulating at a fixed time-step. Produced by the
+/// `interpolated_local_to_world_system` from `PreviousLocalToWorld` and `LocalToWorld`.
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[shrinkwrap(mutable)]
+pub struct InterpolatedLocalToWorld(pub Matrix4<Float>);
+
+impl InterpolatedLocalToWorld {
+    #[inline(always)]
+    pub fn identity() -> Self {
+        Self(Matrix4::identity())
+    }
+}
+
+impl Default for InterpolatedLocalToWorld {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl fmt::Display for InterpolatedLocalToWorld {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
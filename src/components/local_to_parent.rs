@@ -1,15 +1,21 @@
-use crate::math::Matrix4;
+use crate::{decompose::Decomposition, math::Matrix4, Float};
+use serde::{Deserialize, Serialize};
 use shrinkwraprs::Shrinkwrap;
 use std::fmt;
 
-#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy)]
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 #[shrinkwrap(mutable)]
-pub struct LocalToParent(pub Matrix4<f32>);
+pub struct LocalToParent(pub Matrix4<Float>);
 
 impl LocalToParent {
     pub fn identity() -> Self {
         Self(Matrix4::identity())
     }
+
+    /// Splits the matrix back into a translation, rotation and (non-uniform) scale.
+    pub fn decompose(&self) -> Decomposition {
+        Decomposition::of(&self.0)
+    }
 }
 
 impl Default for LocalToParent {
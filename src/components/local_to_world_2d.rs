@@ -0,0 +1,27 @@
+use crate::{math::Matrix3, Float};
+use serde::{Deserialize, Serialize};
+use shrinkwraprs::Shrinkwrap;
+use std::fmt;
+
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[shrinkwrap(mutable)]
+pub struct LocalToWorld2D(pub Matrix3<Float>);
+
+impl LocalToWorld2D {
+    #[inline(always)]
+    pub fn identity() -> Self {
+        Self(Matrix3::identity())
+    }
+}
+
+impl Default for LocalToWorld2D {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl fmt::Display for LocalToWorld2D {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{
+    ecs::*,
+    math::{UnitQuaternion, Vector3},
+    Float,
+};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// What a `LookAt` aims at.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum LookAtTarget {
+    /// The world-space position of an entity (from its `LocalToWorld`).
+    Entity(Entity),
+    /// A world-space position.
+    Point(Vector3<Float>),
+}
+
+/// The local axis of an entity that a `LookAt` points towards the target.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum AimAxis {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl AimAxis {
+    pub fn vector(self) -> Vector3<Float> {
+        match self {
+            AimAxis::PositiveX => Vector3::x(),
+            AimAxis::NegativeX => -Vector3::x(),
+            AimAxis::PositiveY => Vector3::y(),
+            AimAxis::NegativeY => -Vector3::y(),
+            AimAxis::PositiveZ => Vector3::z(),
+            AimAxis::NegativeZ => -Vector3::z(),
+        }
+    }
+
+    /// The rotation taking this axis onto `+Z`.
+    pub fn to_positive_z(self) -> UnitQuaternion<Float> {
+        UnitQuaternion::rotation_between(&self.vector(), &Vector3::z()).unwrap_or_else(|| {
+            // Only `NegativeZ` is opposite to `+Z`.
+            UnitQuaternion::from_axis_angle(&Vector3::y_axis(), PI as Float)
+        })
+    }
+}
+
+impl Default for AimAxis {
+    fn default() -> Self {
+        AimAxis::PositiveZ
+    }
+}
+
+/// Aims the `Rotation` of an entity at a target, see the `look_at_system`. The entity must also
+/// have a `Rotation`.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct LookAt {
+    pub target: LookAtTarget,
+    /// The world-space up direction, which fixes the roll around the aim axis.
+    pub up: Vector3<Float>,
+    /// The local axis that points at the target.
+    pub axis: AimAxis,
+    /// How far to turn towards the target, from 0 (the parent's orientation, ie. an identity
+    /// `Rotation`) to 1 (facing the target).
+    pub weight: Float,
+    /// The largest angle (in radians) to turn away from the parent's orientation.
+    pub max_angle: Option<Float>,
+}
+
+impl LookAt {
+    /// Fully faces `target` along `+Z`, with `+Y` up.
+    pub fn new(target: LookAtTarget) -> Self {
+        Self {
+            target,
+            up: Vector3::y(),
+            axis: AimAxis::default(),
+            weight: 1.0,
+            max_angle: None,
+        }
+    }
+}
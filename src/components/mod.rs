@@ -1,17 +1,45 @@
+mod billboard;
 mod children;
+mod constraints;
+mod interpolated_local_to_world;
 mod local_to_parent;
+mod local_to_parent_2d;
 mod local_to_world;
+mod local_to_world_2d;
+mod look_at;
+mod name;
 mod non_uniform_scale;
 mod parent;
+mod previous_local_to_world;
 mod rotation;
+mod rotation_2d;
 mod scale;
+mod scale_2d;
 mod translation;
+mod translation_2d;
+mod world_to_local;
+mod world_transform;
+mod world_velocity;
 
+pub use billboard::*;
 pub use children::Children;
+pub use constraints::*;
+pub use interpolated_local_to_world::*;
 pub use local_to_parent::*;
+pub use local_to_parent_2d::*;
 pub use local_to_world::*;
+pub use local_to_world_2d::*;
+pub use look_at::*;
+pub use name::*;
 pub use non_uniform_scale::*;
 pub use parent::{Parent, PreviousParent};
+pub use previous_local_to_world::*;
 pub use rotation::*;
+pub use rotation_2d::*;
 pub use scale::*;
+pub use scale_2d::*;
 pub use translation::*;
+pub use translation_2d::*;
+pub use world_to_local::*;
+pub use world_transform::*;
+pub use world_velocity::*;
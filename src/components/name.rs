@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+use shrinkwraprs::Shrinkwrap;
+use std::fmt;
+
+/// A human readable name, eg. from a scene file. Not used by any of the transform systems.
+#[derive(Shrinkwrap, Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[shrinkwrap(mutable)]
+pub struct Name(pub String);
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Self {
+        Self(name.to_string())
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Self(name)
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
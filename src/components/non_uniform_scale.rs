@@ -1,31 +1,32 @@
-use crate::math::Vector3;
+use crate::{math::Vector3, Float};
+use serde::{Deserialize, Serialize};
 use shrinkwraprs::Shrinkwrap;
 use std::fmt;
 
-#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy)]
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 #[shrinkwrap(mutable)]
-pub struct NonUniformScale(pub Vector3<f32>);
+pub struct NonUniformScale(pub Vector3<Float>);
 
 impl NonUniformScale {
-    pub fn new(x: f32, y: f32, z: f32) -> Self {
+    pub fn new(x: Float, y: Float, z: Float) -> Self {
         Self(Vector3::new(x, y, z))
     }
 }
 
-impl From<Vector3<f32>> for NonUniformScale {
-    fn from(scale: Vector3<f32>) -> Self {
+impl From<Vector3<Float>> for NonUniformScale {
+    fn from(scale: Vector3<Float>) -> Self {
         Self(scale)
     }
 }
 
-impl From<&Vector3<f32>> for NonUniformScale {
-    fn from(scale: &Vector3<f32>) -> Self {
+impl From<&Vector3<Float>> for NonUniformScale {
+    fn from(scale: &Vector3<Float>) -> Self {
         Self(*scale)
     }
 }
 
-impl From<&mut Vector3<f32>> for NonUniformScale {
-    fn from(scale: &mut Vector3<f32>) -> Self {
+impl From<&mut Vector3<Float>> for NonUniformScale {
+    fn from(scale: &mut Vector3<Float>) -> Self {
         Self(*scale)
     }
 }
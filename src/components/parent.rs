@@ -1,10 +1,11 @@
 use crate::ecs::*;
+use serde::{Deserialize, Serialize};
 use shrinkwraprs::Shrinkwrap;
 
-#[derive(Shrinkwrap, Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Shrinkwrap, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[shrinkwrap(mutable)]
 pub struct Parent(pub Entity);
 
-#[derive(Shrinkwrap, Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Shrinkwrap, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[shrinkwrap(mutable)]
 pub struct PreviousParent(pub Option<Entity>);
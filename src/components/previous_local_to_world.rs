@@ -0,0 +1,35 @@
+use crate::{components::LocalToWorld, math::Matrix4, Float};
+use serde::{Deserialize, Serialize};
+use shrinkwraprs::Shrinkwrap;
+use std::fmt;
+
+/// The `LocalToWorld` of the entity as of the previous run of the transform system bundle. This
+/// is opt-in, and should be initialized to the same value as the entity's `LocalToWorld`.
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[shrinkwrap(mutable)]
+pub struct PreviousLocalToWorld(pub Matrix4<Float>);
+
+impl PreviousLocalToWorld {
+    #[inline(always)]
+    pub fn identity() -> Self {
+        Self(Matrix4::identity())
+    }
+}
+
+impl Default for PreviousLocalToWorld {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl From<LocalToWorld> for PreviousLocalToWorld {
+    fn from(local_to_world: LocalToWorld) -> Self {
+        Self(local_to_world.0)
+    }
+}
+
+impl fmt::Display for PreviousLocalToWorld {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
@@ -1,9 +1,10 @@
-use crate::math::UnitQuaternion;
+use crate::{math::UnitQuaternion, Float};
+use serde::{Deserialize, Serialize};
 use shrinkwraprs::Shrinkwrap;
 
-#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy)]
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 #[shrinkwrap(mutable)]
-pub struct Rotation(pub UnitQuaternion<f32>);
+pub struct Rotation(pub UnitQuaternion<Float>);
 impl Rotation {
     #[inline(always)]
     pub fn identity() -> Self {
@@ -11,7 +12,7 @@ impl Rotation {
     }
 
     #[inline(always)]
-    pub fn from_euler_angles(roll: f32, pitch: f32, yaw: f32) -> Self {
+    pub fn from_euler_angles(roll: Float, pitch: Float, yaw: Float) -> Self {
         Self(UnitQuaternion::from_euler_angles(roll, pitch, yaw))
     }
 }
@@ -22,8 +23,8 @@ impl Default for Rotation {
     }
 }
 
-impl From<UnitQuaternion<f32>> for Rotation {
-    fn from(rotation: UnitQuaternion<f32>) -> Self {
+impl From<UnitQuaternion<Float>> for Rotation {
+    fn from(rotation: UnitQuaternion<Float>) -> Self {
         Self(rotation)
     }
 }
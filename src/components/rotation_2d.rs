@@ -0,0 +1,31 @@
+use crate::{math::UnitComplex, Float};
+use serde::{Deserialize, Serialize};
+use shrinkwraprs::Shrinkwrap;
+
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[shrinkwrap(mutable)]
+pub struct Rotation2D(pub UnitComplex<Float>);
+impl Rotation2D {
+    #[inline(always)]
+    pub fn identity() -> Self {
+        Self(UnitComplex::identity())
+    }
+
+    /// Creates a counter-clockwise rotation of `angle` radians.
+    #[inline(always)]
+    pub fn from_angle(angle: Float) -> Self {
+        Self(UnitComplex::new(angle))
+    }
+}
+
+impl Default for Rotation2D {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl From<UnitComplex<Float>> for Rotation2D {
+    fn from(rotation: UnitComplex<Float>) -> Self {
+        Self(rotation)
+    }
+}
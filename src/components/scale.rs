@@ -1,13 +1,15 @@
+use crate::Float;
+use serde::{Deserialize, Serialize};
 use shrinkwraprs::Shrinkwrap;
 use std::fmt;
 
-#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy)]
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 #[shrinkwrap(mutable)]
-pub struct Scale(pub f32);
+pub struct Scale(pub Float);
 
-impl From<f32> for Scale {
+impl From<Float> for Scale {
     #[inline(always)]
-    fn from(scale: f32) -> Self {
+    fn from(scale: Float) -> Self {
         Self(scale)
     }
 }
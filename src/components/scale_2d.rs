@@ -0,0 +1,34 @@
+use crate::Float;
+use serde::{Deserialize, Serialize};
+use shrinkwraprs::Shrinkwrap;
+use std::fmt;
+
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[shrinkwrap(mutable)]
+pub struct Scale2D(pub Float);
+
+impl From<Float> for Scale2D {
+    #[inline(always)]
+    fn from(scale: Float) -> Self {
+        Self(scale)
+    }
+}
+
+impl Scale2D {
+    #[inline(always)]
+    pub fn identity() -> Self {
+        Scale2D(1.0)
+    }
+}
+
+impl Default for Scale2D {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl fmt::Display for Scale2D {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Scale2D({})", self.0)
+    }
+}
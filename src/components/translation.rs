@@ -1,9 +1,13 @@
-use crate::math::{Translation3, Vector3};
+use crate::{
+    math::{Translation3, Vector3},
+    Float,
+};
+use serde::{Deserialize, Serialize};
 use shrinkwraprs::Shrinkwrap;
 
-#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy)]
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 #[shrinkwrap(mutable)]
-pub struct Translation(pub Translation3<f32>);
+pub struct Translation(pub Translation3<Float>);
 
 impl Translation {
     #[inline(always)]
@@ -12,7 +16,7 @@ impl Translation {
     }
 
     #[inline(always)]
-    pub fn new(x: f32, y: f32, z: f32) -> Self {
+    pub fn new(x: Float, y: Float, z: Float) -> Self {
         Self(Translation3::new(x, y, z))
     }
 }
@@ -23,14 +27,14 @@ impl Default for Translation {
     }
 }
 
-impl From<Vector3<f32>> for Translation {
-    fn from(translation: Vector3<f32>) -> Self {
+impl From<Vector3<Float>> for Translation {
+    fn from(translation: Vector3<Float>) -> Self {
         Self(Translation3::from(translation))
     }
 }
 
-impl From<Translation3<f32>> for Translation {
-    fn from(translation: Translation3<f32>) -> Self {
+impl From<Translation3<Float>> for Translation {
+    fn from(translation: Translation3<Float>) -> Self {
         Self(translation)
     }
 }
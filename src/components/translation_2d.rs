@@ -0,0 +1,40 @@
+use crate::{
+    math::{Translation2, Vector2},
+    Float,
+};
+use serde::{Deserialize, Serialize};
+use shrinkwraprs::Shrinkwrap;
+
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[shrinkwrap(mutable)]
+pub struct Translation2D(pub Translation2<Float>);
+
+impl Translation2D {
+    #[inline(always)]
+    pub fn identity() -> Self {
+        Self(Translation2::identity())
+    }
+
+    #[inline(always)]
+    pub fn new(x: Float, y: Float) -> Self {
+        Self(Translation2::new(x, y))
+    }
+}
+
+impl Default for Translation2D {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl From<Vector2<Float>> for Translation2D {
+    fn from(translation: Vector2<Float>) -> Self {
+        Self(Translation2::from(translation))
+    }
+}
+
+impl From<Translation2<Float>> for Translation2D {
+    fn from(translation: Translation2<Float>) -> Self {
+        Self(translation)
+    }
+}
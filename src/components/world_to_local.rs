@@ -0,0 +1,36 @@
+use crate::{math::Matrix4, Float};
+use serde::{Deserialize, Serialize};
+use shrinkwraprs::Shrinkwrap;
+use std::fmt;
+
+/// The inverse of `LocalToWorld`, kept up to date by the `world_to_local_system` for entities that
+/// opt-in by having this component. A `LocalToWorld` that cannot be inverted (for example one with
+/// a zero scale) produces an all-zero `WorldToLocal`, see `WorldToLocal::is_invertible`.
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[shrinkwrap(mutable)]
+pub struct WorldToLocal(pub Matrix4<Float>);
+
+impl WorldToLocal {
+    #[inline(always)]
+    pub fn identity() -> Self {
+        Self(Matrix4::identity())
+    }
+
+    /// Returns false if this was computed from a `LocalToWorld` that has no inverse.
+    #[inline(always)]
+    pub fn is_invertible(&self) -> bool {
+        self.0 != Matrix4::zeros()
+    }
+}
+
+impl Default for WorldToLocal {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl fmt::Display for WorldToLocal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
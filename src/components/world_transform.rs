@@ -0,0 +1,40 @@
+use crate::{
+    math::{UnitQuaternion, Vector3},
+    Float,
+};
+use serde::{Deserialize, Serialize};
+use shrinkwraprs::Shrinkwrap;
+
+/// World-space position, kept in sync with `LocalToWorld` by the `world_transform_system`.
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[shrinkwrap(mutable)]
+pub struct WorldTranslation(pub Vector3<Float>);
+
+impl Default for WorldTranslation {
+    fn default() -> Self {
+        Self(Vector3::zeros())
+    }
+}
+
+/// World-space rotation, kept in sync with `LocalToWorld` by the `world_transform_system`.
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[shrinkwrap(mutable)]
+pub struct WorldRotation(pub UnitQuaternion<Float>);
+
+impl Default for WorldRotation {
+    fn default() -> Self {
+        Self(UnitQuaternion::identity())
+    }
+}
+
+/// World-space scale along each axis (negative along X when mirrored), kept in sync with
+/// `LocalToWorld` by the `world_transform_system`.
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[shrinkwrap(mutable)]
+pub struct WorldScale(pub Vector3<Float>);
+
+impl Default for WorldScale {
+    fn default() -> Self {
+        Self(Vector3::repeat(1.0))
+    }
+}
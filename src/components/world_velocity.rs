@@ -0,0 +1,27 @@
+use crate::{math::Vector3, Float};
+use serde::{Deserialize, Serialize};
+use shrinkwraprs::Shrinkwrap;
+
+/// World-space linear velocity (units per second) derived from `PreviousLocalToWorld` and
+/// `LocalToWorld` by the `world_velocity_system`.
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[shrinkwrap(mutable)]
+pub struct WorldLinearVelocity(pub Vector3<Float>);
+
+impl Default for WorldLinearVelocity {
+    fn default() -> Self {
+        Self(Vector3::zeros())
+    }
+}
+
+/// World-space angular velocity (as a scaled axis, radians per second) derived from
+/// `PreviousLocalToWorld` and `LocalToWorld` by the `world_velocity_system`.
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[shrinkwrap(mutable)]
+pub struct WorldAngularVelocity(pub Vector3<Float>);
+
+impl Default for WorldAngularVelocity {
+    fn default() -> Self {
+        Self(Vector3::zeros())
+    }
+}
@@ -0,0 +1,503 @@
+use crate::{
+    components::*,
+    decompose::decompose,
+    ecs::{systems::ParallelRunnable, world::SubWorld, *},
+    hierarchy,
+    math::{Matrix4, Translation3, UnitQuaternion, Vector3},
+    Float,
+};
+use std::collections::{BTreeSet, HashMap};
+
+/// Solves `PositionConstraint`, `RotationConstraint`, `ScaleConstraint` and `ParentConstraint`.
+/// Part of the transform system bundle, after `LocalToWorld` propagation and followed by the
+/// system from `build_propagate`, which carries the results down to the children of constrained
+/// entities.
+///
+/// A constrained entity starts from the world transform its own `Translation`, `Rotation` and
+/// `Scale` (or `NonUniformScale`) would give it, then its `ParentConstraint`, `PositionConstraint`,
+/// `RotationConstraint` and `ScaleConstraint` are applied in that order. The result is written to
+/// its `LocalToWorld`, and to its `LocalToParent` if it has a parent.
+///
+/// Constraints are solved after their sources (and the ancestors of their sources), so chains of
+/// constraints are resolved within a single run. Otherwise entities are solved in `Entity` order,
+/// which also breaks cycles of constraints deterministically.
+pub fn build() -> impl ParallelRunnable {
+    SystemBuilder::<()>::new("ConstraintSystem")
+        .with_query(Entity::query().filter(
+            component::<PositionConstraint>()
+                | component::<RotationConstraint>()
+                | component::<ScaleConstraint>()
+                | component::<ParentConstraint>(),
+        ))
+        .read_component::<PositionConstraint>()
+        .read_component::<RotationConstraint>()
+        .read_component::<ScaleConstraint>()
+        .read_component::<ParentConstraint>()
+        .read_component::<Parent>()
+        .read_component::<Translation>()
+        .read_component::<Rotation>()
+        .read_component::<Scale>()
+        .read_component::<NonUniformScale>()
+        .write_component::<LocalToParent>()
+        .write_component::<LocalToWorld>()
+        .build(move |_commands, world, _resource, query| {
+            let constrained = query.iter(world).cloned().collect::<BTreeSet<_>>();
+            if constrained.is_empty() {
+                return;
+            }
+
+            let mut solved = HashMap::<Entity, Matrix4<Float>>::new();
+            let mut updates = Vec::new();
+            for entity in solve_order(world, &constrained) {
+                let local_to_world = match solve(world, &solved, entity) {
+                    Some(local_to_world) => local_to_world,
+                    None => continue,
+                };
+                let local_to_parent = match hierarchy::parent(world, entity) {
+                    Some(parent) => match world_matrix(world, &solved, parent)
+                        .and_then(|parent| parent.try_inverse())
+                    {
+                        Some(world_to_parent) => Some(world_to_parent * local_to_world),
+                        None => {
+                            log::warn!(
+                                "The parent of the constrained entity {:?} has no invertible LocalToWorld",
+                                entity
+                            );
+                            continue;
+                        }
+                    },
+                    None => None,
+                };
+                solved.insert(entity, local_to_world);
+                updates.push((entity, local_to_world, local_to_parent));
+            }
+
+            for (entity, local_to_world, local_to_parent) in updates {
+                if let Some(target) = world
+                    .entry_mut(entity)
+                    .and_then(|entry| entry.into_component_mut::<LocalToWorld>().ok())
+                {
+                    *target = LocalToWorld(local_to_world);
+                }
+                if let Some(local_to_parent) = local_to_parent {
+                    if let Some(target) = world
+                        .entry_mut(entity)
+                        .and_then(|entry| entry.into_component_mut::<LocalToParent>().ok())
+                    {
+                        *target = LocalToParent(local_to_parent);
+                    }
+                }
+            }
+        })
+}
+
+/// The second propagation pass, which re-computes the `LocalToWorld` of the descendants of
+/// constrained entities from their solved `LocalToWorld`. Unlike the main propagation, it only
+/// visits those subtrees, so it costs nothing in worlds without constraints.
+pub fn build_propagate() -> impl ParallelRunnable {
+    SystemBuilder::<()>::new("ConstraintPropagateSystem")
+        .with_query(
+            <(Entity, Read<Children>, Read<LocalToWorld>)>::query().filter(
+                component::<PositionConstraint>()
+                    | component::<RotationConstraint>()
+                    | component::<ScaleConstraint>()
+                    | component::<ParentConstraint>(),
+            ),
+        )
+        .read_component::<Children>()
+        .read_component::<Parent>()
+        .read_component::<LocalToParent>()
+        .write_component::<LocalToWorld>()
+        .build(move |_commands, world, _resource, query| {
+            let constrained = query
+                .iter(world)
+                .map(|(entity, children, local_to_world)| {
+                    (*entity, children.0.clone(), *local_to_world)
+                })
+                .collect::<Vec<_>>();
+            if constrained.is_empty() {
+                return;
+            }
+
+            let entities = constrained
+                .iter()
+                .map(|(entity, _, _)| *entity)
+                .collect::<BTreeSet<_>>();
+            let mut updates = Vec::new();
+            for (entity, children, local_to_world) in &constrained {
+                // Already covered by a constrained ancestor's subtree.
+                if hierarchy::ancestors(world, *entity).any(|ancestor| entities.contains(&ancestor))
+                {
+                    continue;
+                }
+                for child in children.iter() {
+                    propagate_recursive(world, &local_to_world.0, *child, &mut updates);
+                }
+            }
+
+            for (entity, local_to_world) in updates {
+                if let Some(target) = world
+                    .entry_mut(entity)
+                    .and_then(|entry| entry.into_component_mut::<LocalToWorld>().ok())
+                {
+                    *target = LocalToWorld(local_to_world);
+                }
+            }
+        })
+}
+
+fn propagate_recursive(
+    world: &SubWorld,
+    parent_local_to_world: &Matrix4<Float>,
+    entity: Entity,
+    updates: &mut Vec<(Entity, Matrix4<Float>)>,
+) {
+    let local_to_parent = match world
+        .entry_ref(entity)
+        .and_then(|entry| entry.into_component::<LocalToParent>().ok())
+    {
+        Some(local_to_parent) => local_to_parent.0,
+        None => {
+            log::warn!(
+                "Entity {:?} is a child in the hierarchy but does not have a LocalToParent",
+                entity
+            );
+            return;
+        }
+    };
+    let local_to_world = parent_local_to_world * local_to_parent;
+    updates.push((entity, local_to_world));
+    for child in hierarchy::children(world, entity) {
+        propagate_recursive(world, &local_to_world, child, updates);
+    }
+}
+
+/// Orders `constrained` so that every entity comes after the constrained entities it depends on,
+/// ie. its sources, the ancestors of its sources and its own ancestors.
+fn solve_order(world: &SubWorld, constrained: &BTreeSet<Entity>) -> Vec<Entity> {
+    let dependencies = constrained
+        .iter()
+        .map(|entity| {
+            let mut dependencies = sources(world, *entity)
+                .into_iter()
+                .flat_map(|source| {
+                    std::iter::once(source).chain(hierarchy::ancestors(world, source))
+                })
+                .chain(hierarchy::ancestors(world, *entity))
+                .filter(|dependency| dependency != entity && constrained.contains(dependency))
+                .collect::<Vec<_>>();
+            dependencies.sort();
+            dependencies.dedup();
+            (*entity, dependencies)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut remaining = constrained.clone();
+    let mut order = Vec::with_capacity(constrained.len());
+    while !remaining.is_empty() {
+        let next = remaining
+            .iter()
+            .find(|entity| {
+                dependencies[*entity]
+                    .iter()
+                    .all(|dependency| !remaining.contains(dependency))
+            })
+            .cloned()
+            .unwrap_or_else(|| {
+                let entity = *remaining.iter().next().unwrap();
+                log::warn!(
+                    "The constraints of {:?} depend on each other in a cycle, solving it first",
+                    entity
+                );
+                entity
+            });
+        remaining.remove(&next);
+        order.push(next);
+    }
+    order
+}
+
+fn sources(world: &SubWorld, entity: Entity) -> Vec<Entity> {
+    let entry = match world.entry_ref(entity) {
+        Some(entry) => entry,
+        None => return Vec::new(),
+    };
+    let mut sources = Vec::new();
+    if let Ok(constraint) = entry.get_component::<ParentConstraint>() {
+        sources.extend(constraint.sources.iter().map(|source| source.entity));
+    }
+    if let Ok(constraint) = entry.get_component::<PositionConstraint>() {
+        sources.extend(constraint.sources.iter().map(|source| source.entity));
+    }
+    if let Ok(constraint) = entry.get_component::<RotationConstraint>() {
+        sources.extend(constraint.sources.iter().map(|source| source.entity));
+    }
+    if let Ok(constraint) = entry.get_component::<ScaleConstraint>() {
+        sources.extend(constraint.sources.iter().map(|source| source.entity));
+    }
+    sources
+}
+
+/// The up to date `LocalToWorld` of `entity`, taking the constraints solved so far into account.
+fn world_matrix(
+    world: &SubWorld,
+    solved: &HashMap<Entity, Matrix4<Float>>,
+    entity: Entity,
+) -> Option<Matrix4<Float>> {
+    if let Some(local_to_world) = solved.get(&entity) {
+        return Some(*local_to_world);
+    }
+    let entry = world.entry_ref(entity)?;
+    match hierarchy::parent(world, entity) {
+        Some(parent) => Some(
+            world_matrix(world, solved, parent)? * entry.get_component::<LocalToParent>().ok()?.0,
+        ),
+        None => entry
+            .get_component::<LocalToWorld>()
+            .ok()
+            .map(|local_to_world| local_to_world.0),
+    }
+}
+
+/// The constrained `LocalToWorld` of `entity`.
+fn solve(
+    world: &SubWorld,
+    solved: &HashMap<Entity, Matrix4<Float>>,
+    entity: Entity,
+) -> Option<Matrix4<Float>> {
+    let entry = world.entry_ref(entity)?;
+
+    // The transform the entity would have without constraints. This is built from its own
+    // components rather than read back from `LocalToParent`, which holds last run's result.
+    let mut local = Matrix4::identity();
+    if let Ok(translation) = entry.get_component::<Translation>() {
+        local *= translation.to_homogeneous();
+    }
+    if let Ok(rotation) = entry.get_component::<Rotation>() {
+        local *= rotation.to_homogeneous();
+    }
+    if let Ok(scale) = entry.get_component::<Scale>() {
+        local *= Matrix4::new_scaling(scale.0);
+    }
+    if let Ok(scale) = entry.get_component::<NonUniformScale>() {
+        local *= Matrix4::new_nonuniform_scaling(&scale.0);
+    }
+    let parent_to_world = match hierarchy::parent(world, entity) {
+        Some(parent) => world_matrix(world, solved, parent)?,
+        None => Matrix4::identity(),
+    };
+    let (mut translation, mut rotation, mut scale) = decompose(&(parent_to_world * local));
+
+    // The decomposed world transform of every source, skipping missing ones.
+    let poses = |sources: &[ConstraintSource], offset: &Matrix4<Float>| {
+        sources
+            .iter()
+            .filter_map(|source| {
+                let (translation, rotation, scale) =
+                    decompose(&(world_matrix(world, solved, source.entity)? * offset));
+                Some((translation, rotation, scale, source.weight))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    if let Ok(constraint) = entry.get_component::<ParentConstraint>() {
+        let poses = poses(&constraint.sources, &constraint.offset);
+        let weight = clamp_weight(constraint.weight);
+        if let Some(target) = average(poses.iter().map(|pose| (pose.0, pose.3))) {
+            translation = translation.lerp(&target, weight);
+        }
+        if let Some(target) = blend(poses.iter().map(|pose| (pose.1, pose.3))) {
+            rotation = slerp(&rotation, &target, weight);
+        }
+        if let Some(target) = average(poses.iter().map(|pose| (pose.2, pose.3))) {
+            scale = scale.lerp(&target, weight);
+        }
+    }
+    if let Ok(constraint) = entry.get_component::<PositionConstraint>() {
+        let poses = poses(&constraint.sources, &Matrix4::identity());
+        if let Some(target) = average(poses.iter().map(|pose| (pose.0, pose.3))) {
+            translation = translation.lerp(
+                &(target + constraint.offset),
+                clamp_weight(constraint.weight),
+            );
+        }
+    }
+    if let Ok(constraint) = entry.get_component::<RotationConstraint>() {
+        let poses = poses(&constraint.sources, &Matrix4::identity());
+        if let Some(target) = blend(poses.iter().map(|pose| (pose.1, pose.3))) {
+            rotation = slerp(
+                &rotation,
+                &(target * constraint.offset),
+                clamp_weight(constraint.weight),
+            );
+        }
+    }
+    if let Ok(constraint) = entry.get_component::<ScaleConstraint>() {
+        let poses = poses(&constraint.sources, &Matrix4::identity());
+        if let Some(target) = average(poses.iter().map(|pose| (pose.2, pose.3))) {
+            scale = scale.lerp(&target, clamp_weight(constraint.weight));
+        }
+    }
+
+    Some(
+        Translation3::from(translation).to_homogeneous()
+            * rotation.to_homogeneous()
+            * Matrix4::new_nonuniform_scaling(&scale),
+    )
+}
+
+fn clamp_weight(weight: Float) -> Float {
+    weight.max(0.0).min(1.0)
+}
+
+/// The weighted average of `vectors`, or `None` if the weights don't add up to anything.
+fn average(vectors: impl Iterator<Item = (Vector3<Float>, Float)>) -> Option<Vector3<Float>> {
+    let (sum, total) = vectors
+        .filter(|(_, weight)| *weight > 0.0)
+        .fold((Vector3::zeros(), 0.0), |(sum, total), (vector, weight)| {
+            (sum + vector * weight, total + weight)
+        });
+    if total > 0.0 {
+        Some(sum / total)
+    } else {
+        None
+    }
+}
+
+/// The weighted blend of `rotations`, slerping each one in by its share of the accumulated
+/// weight. Rotations are blended in source order, so the result is deterministic.
+fn blend(
+    rotations: impl Iterator<Item = (UnitQuaternion<Float>, Float)>,
+) -> Option<UnitQuaternion<Float>> {
+    let mut blended = None;
+    let mut total = 0.0;
+    for (rotation, weight) in rotations.filter(|(_, weight)| *weight > 0.0) {
+        total += weight;
+        blended = Some(match blended {
+            Some(blended) => slerp(&blended, &rotation, weight / total),
+            None => rotation,
+        });
+    }
+    blended
+}
+
+/// Like `UnitQuaternion::slerp`, without panicking on opposite rotations (`from` is kept).
+fn slerp(
+    from: &UnitQuaternion<Float>,
+    to: &UnitQuaternion<Float>,
+    t: Float,
+) -> UnitQuaternion<Float> {
+    from.try_slerp(to, t, 1.0e-6).unwrap_or(*from)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn solves_chained_constraints() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+        let mut schedule = Schedule::builder()
+            .add_system(build())
+            .add_system(build_propagate())
+            .build();
+
+        // The `LocalToWorld`s are set as if propagation had already run.
+        let a = world.push((LocalToWorld(Matrix4::new_translation(&Vector3::new(
+            2.0, 0.0, 0.0,
+        ))),));
+        let b = world.push((LocalToWorld(Matrix4::new_translation(&Vector3::new(
+            0.0, 4.0, 0.0,
+        ))),));
+
+        // `d` follows `c`, but is created first so that `Entity` order alone would solve it too
+        // early.
+        let d = world.push((LocalToWorld::identity(),));
+        let c = world.push((
+            LocalToWorld::identity(),
+            PositionConstraint::new(vec![
+                ConstraintSource::new(a, 1.0),
+                ConstraintSource::new(b, 1.0),
+            ]),
+        ));
+        let e = world.push((
+            Parent(d),
+            LocalToParent(Matrix4::new_translation(&Vector3::new(1.0, 0.0, 0.0))),
+            LocalToWorld::identity(),
+        ));
+        {
+            let mut entry = world.entry(d).unwrap();
+            entry.add_component(ParentConstraint {
+                offset: Matrix4::new_translation(&Vector3::new(0.0, 0.0, 1.0)),
+                ..ParentConstraint::new(vec![ConstraintSource::new(c, 1.0)])
+            });
+            entry.add_component(Children::with(&[e]));
+        }
+
+        // A constrained entity with a (rotated) parent gets its `LocalToParent` solved too.
+        let rotated = UnitQuaternion::from_euler_angles(0.0, 0.0, 1.0).to_homogeneous();
+        let p = world.push((LocalToWorld(
+            Matrix4::new_translation(&Vector3::new(0.0, 0.0, 5.0)) * rotated,
+        ),));
+        let f = world.push((
+            Parent(p),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            PositionConstraint::new(vec![ConstraintSource::new(a, 1.0)]),
+        ));
+        let g = world.push((
+            Parent(f),
+            LocalToParent(Matrix4::new_translation(&Vector3::new(0.0, 1.0, 0.0))),
+            LocalToWorld::identity(),
+        ));
+        world.entry(p).unwrap().add_component(Children::with(&[f]));
+        world.entry(f).unwrap().add_component(Children::with(&[g]));
+
+        schedule.execute(&mut world, &mut resources);
+
+        let translation = |entity| {
+            let local_to_world = world
+                .entry_ref(entity)
+                .unwrap()
+                .get_component::<LocalToWorld>()
+                .unwrap()
+                .0;
+            Vector3::new(
+                local_to_world[(0, 3)],
+                local_to_world[(1, 3)],
+                local_to_world[(2, 3)],
+            )
+        };
+        assert!((translation(c) - Vector3::new(1.0, 2.0, 0.0)).norm() < 1e-5);
+        assert!((translation(d) - Vector3::new(1.0, 2.0, 1.0)).norm() < 1e-5);
+        // Carried down by the second propagation pass.
+        assert!((translation(e) - Vector3::new(2.0, 2.0, 1.0)).norm() < 1e-5);
+
+        assert!((translation(f) - Vector3::new(2.0, 0.0, 0.0)).norm() < 1e-5);
+        let local_to_parent = world
+            .entry_ref(f)
+            .unwrap()
+            .get_component::<LocalToParent>()
+            .unwrap()
+            .0;
+        let p_local_to_world = world
+            .entry_ref(p)
+            .unwrap()
+            .get_component::<LocalToWorld>()
+            .unwrap()
+            .0;
+        let f_local_to_world = world
+            .entry_ref(f)
+            .unwrap()
+            .get_component::<LocalToWorld>()
+            .unwrap()
+            .0;
+        assert!((p_local_to_world * local_to_parent - f_local_to_world).norm() < 1e-5);
+        // `f` keeps the parent's rotation, so its child is offset along the rotated Y axis.
+        let offset = rotated.transform_vector(&Vector3::y());
+        assert!((translation(g) - (Vector3::new(2.0, 0.0, 0.0) + offset)).norm() < 1e-5);
+    }
+}
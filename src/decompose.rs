@@ -0,0 +1,125 @@
+//! Splitting transform matrices back into `Translation`, `Rotation` and `NonUniformScale`.
+use crate::{
+    components::*,
+    ecs::*,
+    math::{
+        Matrix3, Matrix4, Rotation3, UnitComplex, UnitQuaternion, Vector2, Vector3, U1, U2, U3,
+    },
+    Float,
+};
+
+/// A `LocalToWorld` or `LocalToParent` split back into components, such that
+/// `matrix = translation * rotation * scale` (unless there is `shear`).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Decomposition {
+    pub translation: Translation,
+    pub rotation: Rotation,
+    /// Negative along X when the matrix mirrors (has a negative determinant).
+    pub scale: NonUniformScale,
+    /// How far the axes of the matrix are from being perpendicular, as the largest absolute cosine
+    /// of the angle between two of them. This is zero for any matrix built from `Translation`,
+    /// `Rotation` and `NonUniformScale`, but a non-uniformly scaled parent with a rotated child
+    /// shears the child's `LocalToWorld`. The other fields are an approximation when it isn't zero.
+    pub shear: Float,
+}
+
+impl Decomposition {
+    pub fn of(matrix: &Matrix4<Float>) -> Self {
+        let (translation, rotation, scale) = decompose(matrix);
+        Self {
+            translation: Translation::from(translation),
+            rotation: Rotation::from(rotation),
+            scale: NonUniformScale::from(scale),
+            shear: shear(matrix),
+        }
+    }
+}
+
+/// Splits an affine homogeneous matrix back into a translation, rotation and (possibly negative)
+/// non-uniform scale such that `matrix = T * R * S`. A mirroring matrix (negative determinant) is
+/// represented by negating the X scale. Zero-length axes are left un-normalized.
+pub(crate) fn decompose(
+    matrix: &Matrix4<Float>,
+) -> (Vector3<Float>, UnitQuaternion<Float>, Vector3<Float>) {
+    let translation = matrix.fixed_slice::<U3, U1>(0, 3).into_owned();
+    let mut basis = matrix.fixed_slice::<U3, U3>(0, 0).into_owned();
+
+    let mut scale = Vector3::new(
+        basis.column(0).norm(),
+        basis.column(1).norm(),
+        basis.column(2).norm(),
+    );
+    if basis.determinant() < 0.0 {
+        scale.x = -scale.x;
+    }
+
+    for (i, mut column) in basis.column_iter_mut().enumerate() {
+        if scale[i] != 0.0 {
+            column /= scale[i];
+        }
+    }
+
+    let rotation = UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_unchecked(basis));
+    (translation, rotation, scale)
+}
+
+/// How far the axes of `matrix` are from being perpendicular, as the largest absolute cosine of the
+/// angle between two of them. Zero for any `T * R * S` matrix, so anything larger is shear that
+/// `decompose` cannot represent.
+pub(crate) fn shear(matrix: &Matrix4<Float>) -> Float {
+    let axes = [
+        matrix.fixed_slice::<U3, U1>(0, 0).normalize(),
+        matrix.fixed_slice::<U3, U1>(0, 1).normalize(),
+        matrix.fixed_slice::<U3, U1>(0, 2).normalize(),
+    ];
+    axes[0]
+        .dot(&axes[1])
+        .abs()
+        .max(axes[0].dot(&axes[2]).abs())
+        .max(axes[1].dot(&axes[2]).abs())
+}
+
+/// Whether a (decomposed) scale is the same along every axis, and can be stored as a `Scale`.
+pub(crate) fn is_uniform(scale: &Vector3<Float>) -> bool {
+    let tolerance = 1.0e-5 * scale.x.abs().max(1.0);
+    (scale.x - scale.y).abs() <= tolerance && (scale.x - scale.z).abs() <= tolerance
+}
+
+/// Splits an affine homogeneous 2D matrix into a translation, rotation and uniform scale. The
+/// scale is the square root of the (absolute) determinant, so mirroring and non-uniform scaling
+/// are lost.
+pub(crate) fn decompose_2d(matrix: &Matrix3<Float>) -> (Vector2<Float>, UnitComplex<Float>, Float) {
+    let translation = Vector2::new(matrix[(0, 2)], matrix[(1, 2)]);
+    let rotation = UnitComplex::new(matrix[(1, 0)].atan2(matrix[(0, 0)]));
+    let scale = matrix
+        .fixed_slice::<U2, U2>(0, 0)
+        .determinant()
+        .abs()
+        .sqrt();
+    (translation, rotation, scale)
+}
+
+/// Records commands setting the `Translation`, `Rotation` and `Scale` (or `NonUniformScale`, when
+/// the axes are scaled differently) of `entity` to the decomposition of `matrix`.
+pub(crate) fn bake_trs(commands: &mut CommandBuffer, entity: Entity, matrix: &Matrix4<Float>) {
+    let (translation, rotation, scale) = decompose(matrix);
+    commands.add_component(entity, Translation::from(translation));
+    commands.add_component(entity, Rotation::from(rotation));
+
+    if is_uniform(&scale) {
+        commands.remove_component::<NonUniformScale>(entity);
+        commands.add_component(entity, Scale(scale.x));
+    } else {
+        commands.remove_component::<Scale>(entity);
+        commands.add_component(entity, NonUniformScale::from(scale));
+    }
+}
+
+/// Records commands setting the `Translation2D`, `Rotation2D` and `Scale2D` of `entity` to the
+/// decomposition of `matrix`.
+pub(crate) fn bake_trs_2d(commands: &mut CommandBuffer, entity: Entity, matrix: &Matrix3<Float>) {
+    let (translation, rotation, scale) = decompose_2d(matrix);
+    commands.add_component(entity, Translation2D::from(translation));
+    commands.add_component(entity, Rotation2D::from(rotation));
+    commands.add_component(entity, Scale2D(scale));
+}
@@ -0,0 +1,167 @@
+//! Deleting an entity along with its whole hierarchy subtree. The subtree is found by following
+//! `Children` down from the entity, so its cost only depends on the size of the subtree. Descendants
+//! parented since the transform system bundle last ran aren't in `Children` yet and are left
+//! behind, with a `Parent` to a deleted entity (see `parent_update_system::build_cleanup`).
+use crate::{components::*, ecs::*, hierarchy};
+use std::collections::HashSet;
+
+/// Deletes `entity` and all of its descendants from `world`, and removes `entity` from the
+/// `Children` of its parent (if that survives).
+pub fn despawn_recursive(world: &mut World, entity: Entity) {
+    if let Some(parent) = hierarchy::parent(world, entity) {
+        if let Some(children) = world
+            .entry_mut(parent)
+            .and_then(|entry| entry.into_component_mut::<Children>().ok())
+        {
+            children.0.retain(|child| *child != entity);
+        }
+    }
+
+    for entity in subtree(world, entity) {
+        world.remove(entity);
+    }
+}
+
+/// Like `despawn_recursive`, but records the deletions in `commands` instead. The subtree is read
+/// from `world`, which can be the `SubWorld` of a system that declares read access to `Parent`
+/// and `Children`.
+pub fn despawn_recursive_deferred<W: EntityStore>(
+    world: &W,
+    commands: &mut CommandBuffer,
+    entity: Entity,
+) {
+    if let Some(parent) = hierarchy::parent(world, entity) {
+        // Removed from the `Children` current at flush time, so despawning several siblings
+        // before a flush removes all of them.
+        commands.exec_mut(move |world| {
+            if let Some(children) = world
+                .entry_mut(parent)
+                .and_then(|entry| entry.into_component_mut::<Children>().ok())
+            {
+                children.0.retain(|child| *child != entity);
+            }
+        });
+    }
+
+    for entity in subtree(world, entity) {
+        commands.remove(entity);
+    }
+}
+
+/// `entity` followed by all of its descendants.
+pub(crate) fn subtree<W: EntityStore>(world: &W, entity: Entity) -> Vec<Entity> {
+    let mut subtree = vec![entity];
+    let mut visited = subtree.iter().cloned().collect::<HashSet<_>>();
+    let mut next = 0;
+    while next < subtree.len() {
+        // Each entity is visited once, even if `Children` forms a cycle.
+        let children = hierarchy::children(world, subtree[next]);
+        subtree.extend(children.into_iter().filter(|child| visited.insert(*child)));
+        next += 1;
+    }
+    subtree
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn despawns_subtree() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut world = World::default();
+
+        // root
+        //  ├─ a
+        //  │  └─ c
+        //  └─ b
+        let root = world.push((LocalToWorld::identity(), Translation::identity()));
+        let a = world.push((LocalToWorld::identity(), Parent(root)));
+        let b = world.push((LocalToWorld::identity(), Parent(root)));
+        let c = world.push((LocalToWorld::identity(), Parent(a)));
+        world
+            .entry(root)
+            .unwrap()
+            .add_component(Children::with(&[a, b]));
+        world.entry(a).unwrap().add_component(Children::with(&[c]));
+
+        despawn_recursive(&mut world, a);
+
+        assert!(world.entry(a).is_none());
+        assert!(world.entry(c).is_none());
+        assert!(world.entry(b).is_some());
+        assert_eq!(
+            world
+                .entry(root)
+                .unwrap()
+                .get_component::<Children>()
+                .unwrap()
+                .0
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![b]
+        );
+    }
+
+    #[test]
+    fn despawns_subtree_deferred() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut world = World::default();
+
+        // root
+        //  ├─ a
+        //  │  └─ c
+        //  └─ b
+        let root = world.push((LocalToWorld::identity(), Translation::identity()));
+        let a = world.push((LocalToWorld::identity(), Parent(root)));
+        let b = world.push((LocalToWorld::identity(), Parent(root)));
+        let c = world.push((LocalToWorld::identity(), Parent(a)));
+        world
+            .entry(root)
+            .unwrap()
+            .add_component(Children::with(&[a, b]));
+        world.entry(a).unwrap().add_component(Children::with(&[c]));
+
+        let mut commands = CommandBuffer::new(&world);
+        despawn_recursive_deferred(&world, &mut commands, a);
+
+        // Nothing changes until the commands are flushed.
+        assert!(world.entry(a).is_some());
+        assert_eq!(hierarchy::children(&world, root).to_vec(), vec![a, b]);
+
+        commands.flush(&mut world);
+
+        assert!(world.entry(a).is_none());
+        assert!(world.entry(c).is_none());
+        assert!(world.entry(b).is_some());
+        assert_eq!(hierarchy::children(&world, root).to_vec(), vec![b]);
+    }
+
+    #[test]
+    fn despawns_siblings_deferred() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut world = World::default();
+        let root = world.push((LocalToWorld::identity(), Translation::identity()));
+        let a = world.push((LocalToWorld::identity(), Parent(root)));
+        let b = world.push((LocalToWorld::identity(), Parent(root)));
+        let c = world.push((LocalToWorld::identity(), Parent(root)));
+        world
+            .entry(root)
+            .unwrap()
+            .add_component(Children::with(&[a, b, c]));
+
+        // Both are recorded against the same, unflushed `Children`.
+        let mut commands = CommandBuffer::new(&world);
+        despawn_recursive_deferred(&world, &mut commands, a);
+        despawn_recursive_deferred(&world, &mut commands, c);
+        commands.flush(&mut world);
+
+        assert!(world.entry(a).is_none());
+        assert!(world.entry(c).is_none());
+        assert_eq!(hierarchy::children(&world, root).to_vec(), vec![b]);
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{
+    components::*,
+    ecs::{systems::ParallelRunnable, *},
+    resources::*,
+};
+
+/// Shifts every root of the world back towards the origin when the `FloatingOrigin` focus strays
+/// further than the threshold from it. Requires the `FloatingOrigin` and `OriginShiftEvents`
+/// resources, and should be scheduled before the transform system bundle so that the shifted
+/// roots are picked up by the `local_to_world_system` (and then their children by the
+/// `local_to_world_propagate_system`) in the same frame.
+///
+/// The `LocalToWorld` and `PreviousLocalToWorld` of every entity are shifted along with the
+/// roots, so that the `PreviousLocalToWorld` snapshot taken by the bundle is in the shifted space
+/// too, and velocities and interpolation don't see the shift as movement.
+///
+/// Roots placed by a `Rotation` or scale without a `Translation` are given one holding the shift,
+/// otherwise they would jump back as soon as their `LocalToWorld` is re-computed.
+pub fn build() -> impl ParallelRunnable {
+    SystemBuilder::<()>::new("FloatingOriginSystem")
+        .write_resource::<FloatingOrigin>()
+        .write_resource::<OriginShiftEvents>()
+        // Roots with a `Translation` (ie. without a `Parent`).
+        .with_query(<Write<Translation>>::query().filter(!component::<Parent>()))
+        // Roots with a pre-baked `LocalToWorld` and no space transform components at all.
+        .with_query(<Write<LocalToWorld>>::query().filter(
+            !component::<Parent>()
+                & !component::<Translation>()
+                & !component::<Rotation>()
+                & !component::<Scale>()
+                & !component::<NonUniformScale>(),
+        ))
+        // Every other `LocalToWorld`, which is re-computed this frame but shifted right away for
+        // the `previous_local_to_world_system`.
+        .with_query(<Write<LocalToWorld>>::query().filter(
+            component::<Parent>()
+                | component::<Translation>()
+                | component::<Rotation>()
+                | component::<Scale>()
+                | component::<NonUniformScale>(),
+        ))
+        .with_query(<Write<PreviousLocalToWorld>>::query())
+        // Roots with a space transform but no `Translation` to shift.
+        .with_query(Entity::query().filter(
+            !component::<Parent>()
+                & !component::<Translation>()
+                & (component::<Rotation>() | component::<Scale>() | component::<NonUniformScale>()),
+        ))
+        .read_component::<LocalToWorld>()
+        .build(move |commands, world, (origin, events), queries| {
+            // Events only live for a single run.
+            events.0.clear();
+
+            let focus = match origin.focus {
+                Some(focus) => focus,
+                None => return,
+            };
+
+            // The focus' world position as of the last transform update.
+            let focus_position = if let Some(local_to_world) = world
+                .entry_ref(focus)
+                .and_then(|entry| entry.into_component::<LocalToWorld>().ok())
+            {
+                local_to_world.column(3).xyz()
+            } else {
+                log::warn!(
+                    "The FloatingOrigin focus {:?} does not have a LocalToWorld",
+                    focus
+                );
+                return;
+            };
+
+            if focus_position.norm() <= origin.threshold {
+                return;
+            }
+
+            log::trace!("Shifting the world origin by {}", focus_position);
+            for translation in queries.0.iter_mut(world) {
+                translation.0.vector -= focus_position;
+            }
+            for local_to_world in queries.1.iter_mut(world) {
+                local_to_world.0 = local_to_world.0.append_translation(&-focus_position);
+            }
+            for local_to_world in queries.2.iter_mut(world) {
+                local_to_world.0 = local_to_world.0.append_translation(&-focus_position);
+            }
+            for previous_local_to_world in queries.3.iter_mut(world) {
+                previous_local_to_world.0 = previous_local_to_world
+                    .0
+                    .append_translation(&-focus_position);
+            }
+
+            for entity in queries.4.iter(world) {
+                commands.add_component(*entity, Translation::from(-focus_position));
+            }
+
+            origin.origin += focus_position;
+            events.0.push(OriginShifted {
+                offset: focus_position,
+            });
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        local_to_parent_system, local_to_world_propagate_system, local_to_world_system,
+        math::Vector3, missing_previous_parent_system, parent_update_system,
+    };
+
+    #[test]
+    fn shifts_roots_past_threshold() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+
+        let mut schedule = Schedule::builder()
+            .add_system(build())
+            .add_system(missing_previous_parent_system::build())
+            .flush()
+            .add_system(parent_update_system::build())
+            .flush()
+            .add_system(local_to_parent_system::build())
+            .flush()
+            .add_system(local_to_world_system::build())
+            .flush()
+            .add_system(local_to_world_propagate_system::build())
+            .build();
+
+        let focus = world.push((Translation::new(10.0, 0.0, 0.0), LocalToWorld::identity()));
+        let other = world.push((Translation::new(0.0, 5.0, 0.0), LocalToWorld::identity()));
+        let child = world.push((
+            Translation::new(0.0, 0.0, 1.0),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            Parent(focus),
+        ));
+
+        resources.insert(FloatingOrigin::new(focus, 100.0));
+        resources.insert(OriginShiftEvents::default());
+
+        // The focus' `LocalToWorld` is computed on the first run, and is within the threshold.
+        schedule.execute(&mut world, &mut resources);
+        assert!(resources.get::<OriginShiftEvents>().unwrap().0.is_empty());
+
+        // Move the focus past the threshold, the shift happens the run after its `LocalToWorld`
+        // was updated.
+        *world
+            .entry_mut(focus)
+            .unwrap()
+            .get_component_mut::<Translation>()
+            .unwrap() = Translation::new(200.0, 0.0, 0.0);
+        schedule.execute(&mut world, &mut resources);
+        schedule.execute(&mut world, &mut resources);
+
+        assert_eq!(
+            resources.get::<OriginShiftEvents>().unwrap().0,
+            vec![OriginShifted {
+                offset: Vector3::new(200.0, 0.0, 0.0)
+            }]
+        );
+        assert_eq!(
+            resources.get::<FloatingOrigin>().unwrap().origin,
+            Vector3::new(200.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            *world
+                .entry(other)
+                .unwrap()
+                .get_component::<Translation>()
+                .unwrap(),
+            Translation::new(-200.0, 5.0, 0.0)
+        );
+
+        // Children keep their local translation and are re-propagated from the shifted root.
+        assert_eq!(
+            *world
+                .entry(child)
+                .unwrap()
+                .get_component::<Translation>()
+                .unwrap(),
+            Translation::new(0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            world
+                .entry(child)
+                .unwrap()
+                .get_component::<LocalToWorld>()
+                .unwrap()
+                .0,
+            Translation::new(0.0, 0.0, 1.0).to_homogeneous()
+        );
+    }
+
+    #[test]
+    fn adds_translation_to_untranslated_roots() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+
+        let mut schedule = Schedule::builder()
+            .add_system(build())
+            .flush()
+            .add_system(local_to_world_system::build())
+            .build();
+
+        let rotation = Rotation::from_euler_angles(0.0, 1.0, 0.0);
+        let focus = world.push((Translation::new(200.0, 0.0, 0.0), LocalToWorld::identity()));
+        let rotated = world.push((rotation, LocalToWorld::identity()));
+
+        resources.insert(FloatingOrigin::new(focus, 100.0));
+        resources.insert(OriginShiftEvents::default());
+
+        // The first run computes the focus' `LocalToWorld`, the second shifts.
+        schedule.execute(&mut world, &mut resources);
+        schedule.execute(&mut world, &mut resources);
+        assert_eq!(resources.get::<OriginShiftEvents>().unwrap().0.len(), 1);
+        assert_eq!(
+            *world
+                .entry(rotated)
+                .unwrap()
+                .get_component::<Translation>()
+                .unwrap(),
+            Translation::new(-200.0, 0.0, 0.0)
+        );
+
+        // Changing the rotation re-computes the `LocalToWorld`, which keeps the shift.
+        let rotation = Rotation::from_euler_angles(0.0, 2.0, 0.0);
+        *world
+            .entry_mut(rotated)
+            .unwrap()
+            .get_component_mut::<Rotation>()
+            .unwrap() = rotation;
+        schedule.execute(&mut world, &mut resources);
+        assert_eq!(
+            world
+                .entry(rotated)
+                .unwrap()
+                .get_component::<LocalToWorld>()
+                .unwrap()
+                .0,
+            rotation
+                .to_homogeneous()
+                .append_translation(&Vector3::new(-200.0, 0.0, 0.0))
+        );
+    }
+}
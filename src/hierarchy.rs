@@ -0,0 +1,210 @@
+//! Traversal of the `Parent` / `Children` hierarchy. Every function here is generic over
+//! `EntityStore`, so they can be used on a `World` as well as on the `SubWorld` given to a system
+//! (which must then declare read access to `Parent` and `Children`).
+//!
+//! Walking up the hierarchy follows `Parent`, which is always correct. Walking down follows
+//! `Children`, which is only updated by the transform system bundle and can be out of date after
+//! world mutations.
+use crate::{components::*, ecs::*};
+use smallvec::SmallVec;
+use std::collections::{HashSet, VecDeque};
+
+/// The parent of `entity`, if it has one.
+pub fn parent<W: EntityStore>(world: &W, entity: Entity) -> Option<Entity> {
+    world
+        .entry_ref(entity)
+        .and_then(|entry| entry.into_component::<Parent>().ok())
+        .map(|parent| parent.0)
+}
+
+/// The direct children of `entity` (empty if it has no `Children`).
+pub fn children<W: EntityStore>(world: &W, entity: Entity) -> SmallVec<[Entity; 8]> {
+    world
+        .entry_ref(entity)
+        .and_then(|entry| entry.into_component::<Children>().ok())
+        .map(|children| children.0.clone())
+        .unwrap_or_default()
+}
+
+/// The root of the hierarchy `entity` is a member of (which is `entity` itself if it has no
+/// `Parent`).
+pub fn root<W: EntityStore>(world: &W, entity: Entity) -> Entity {
+    ancestors(world, entity).last().unwrap_or(entity)
+}
+
+/// All the roots of hierarchies in the world, ie. entities with `Children` but no `Parent`.
+pub fn roots<W: EntityStore>(world: &W) -> Vec<Entity> {
+    <(Entity, Read<Children>)>::query()
+        .filter(!component::<Parent>())
+        .iter(world)
+        .map(|(entity, _)| *entity)
+        .collect()
+}
+
+/// The other children of `entity`'s parent, in `Children` order.
+pub fn siblings<W: EntityStore>(world: &W, entity: Entity) -> impl Iterator<Item = Entity> {
+    parent(world, entity)
+        .map(|parent| children(world, parent))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(move |sibling| *sibling != entity)
+}
+
+/// Iterates the ancestors of `entity` by following `Parent`, starting with its parent and ending
+/// with the root. `Parent`s can form a cycle until the `parent_update_system` repairs it, in which
+/// case iteration stops before any entity would be repeated.
+pub fn ancestors<W: EntityStore>(world: &W, entity: Entity) -> Ancestors<W> {
+    Ancestors {
+        world,
+        current: Some(entity),
+        visited: std::iter::once(entity).collect(),
+    }
+}
+
+/// Iterates all descendants of `entity` in depth-first (pre-order) order, along with their depth
+/// relative to `entity` (direct children have a depth of 1).
+pub fn descendants_depth_first<W: EntityStore>(world: &W, entity: Entity) -> DepthFirst<W> {
+    DepthFirst {
+        world,
+        stack: children(world, entity)
+            .into_iter()
+            .rev()
+            .map(|child| (child, 1))
+            .collect(),
+    }
+}
+
+/// Iterates all descendants of `entity` in breadth-first order, along with their depth relative
+/// to `entity` (direct children have a depth of 1).
+pub fn descendants_breadth_first<W: EntityStore>(world: &W, entity: Entity) -> BreadthFirst<W> {
+    BreadthFirst {
+        world,
+        queue: children(world, entity)
+            .into_iter()
+            .map(|child| (child, 1))
+            .collect(),
+    }
+}
+
+pub struct Ancestors<'a, W> {
+    world: &'a W,
+    current: Option<Entity>,
+    visited: HashSet<Entity>,
+}
+
+impl<'a, W: EntityStore> Iterator for Ancestors<'a, W> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        let entity = self.current?;
+        self.current = match parent(self.world, entity) {
+            Some(parent) if !self.visited.insert(parent) => {
+                log::warn!("The Parent of {:?} closes a cycle", entity);
+                None
+            }
+            parent => parent,
+        };
+        self.current
+    }
+}
+
+pub struct DepthFirst<'a, W> {
+    world: &'a W,
+    stack: Vec<(Entity, usize)>,
+}
+
+impl<'a, W: EntityStore> Iterator for DepthFirst<'a, W> {
+    type Item = (Entity, usize);
+
+    fn next(&mut self) -> Option<(Entity, usize)> {
+        let (entity, depth) = self.stack.pop()?;
+        self.stack.extend(
+            children(self.world, entity)
+                .into_iter()
+                .rev()
+                .map(|child| (child, depth + 1)),
+        );
+        Some((entity, depth))
+    }
+}
+
+pub struct BreadthFirst<'a, W> {
+    world: &'a W,
+    queue: VecDeque<(Entity, usize)>,
+}
+
+impl<'a, W: EntityStore> Iterator for BreadthFirst<'a, W> {
+    type Item = (Entity, usize);
+
+    fn next(&mut self) -> Option<(Entity, usize)> {
+        let (entity, depth) = self.queue.pop_front()?;
+        self.queue.extend(
+            children(self.world, entity)
+                .into_iter()
+                .map(|child| (child, depth + 1)),
+        );
+        Some((entity, depth))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn traversal() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut world = World::default();
+
+        // root
+        //  ├─ a
+        //  │  └─ c
+        //  └─ b
+        let root = world.push((LocalToWorld::identity(), Translation::identity()));
+        let a = world.push((LocalToWorld::identity(), Parent(root)));
+        let b = world.push((LocalToWorld::identity(), Parent(root)));
+        let c = world.push((LocalToWorld::identity(), Parent(a)));
+        world
+            .entry(root)
+            .unwrap()
+            .add_component(Children::with(&[a, b]));
+        world.entry(a).unwrap().add_component(Children::with(&[c]));
+
+        assert_eq!(ancestors(&world, c).collect::<Vec<_>>(), vec![a, root]);
+        assert_eq!(ancestors(&world, root).next(), None);
+        assert_eq!(super::root(&world, c), root);
+        assert_eq!(roots(&world), vec![root]);
+        assert_eq!(siblings(&world, a).collect::<Vec<_>>(), vec![b]);
+        assert_eq!(
+            descendants_depth_first(&world, root).collect::<Vec<_>>(),
+            vec![(a, 1), (c, 2), (b, 1)]
+        );
+        assert_eq!(
+            descendants_breadth_first(&world, root).collect::<Vec<_>>(),
+            vec![(a, 1), (b, 1), (c, 2)]
+        );
+    }
+
+    #[test]
+    fn stops_on_cycles() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut world = World::default();
+
+        // A cycle that `parent_update_system` hasn't had a chance to repair yet, with `c` hanging
+        // off it.
+        let a = world.push((LocalToWorld::identity(),));
+        let b = world.push((LocalToWorld::identity(), Parent(a)));
+        world.entry(a).unwrap().add_component(Parent(b));
+        let c = world.push((LocalToWorld::identity(), Parent(a)));
+
+        assert_eq!(ancestors(&world, a).collect::<Vec<_>>(), vec![b]);
+        assert_eq!(ancestors(&world, c).collect::<Vec<_>>(), vec![a, b]);
+        assert_eq!(super::root(&world, c), b);
+        assert_eq!(
+            crate::reparent::insert_child(&mut world, c, 0, a),
+            Err(crate::reparent::ReparentError::Cycle)
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Spawning whole hierarchies at once, into a `World` or a `CommandBuffer`:
+//!
+//! ```ignore
+//! world.spawn_hierarchy((Translation::new(1.0, 0.0, 0.0),), |parent| {
+//!     parent
+//!         .child((Translation::new(0.0, 1.0, 0.0),))
+//!         .with_children((Rotation::identity(),), |parent| {
+//!             parent.child((Scale(2.0),));
+//!         });
+//! });
+//! ```
+//!
+//! Every entity gets a `LocalToWorld`, and every child a `Parent`, `PreviousParent` and
+//! `LocalToParent` (all of which replace any passed in, and are re-computed by the transform system
+//! bundle). Parents get their `Children` right away, so the hierarchy is consistent before the
+//! bundle first runs.
+use crate::{
+    components::*,
+    ecs::{storage::IntoComponentSource, *},
+};
+use smallvec::SmallVec;
+
+/// Somewhere entities can be spawned: a `World`, or a `CommandBuffer` to spawn them later.
+pub trait SpawnTarget {
+    fn spawn<T>(&mut self, components: T) -> Entity
+    where
+        Option<T>: IntoComponentSource;
+
+    fn add<C: storage::Component>(&mut self, entity: Entity, component: C);
+}
+
+impl SpawnTarget for World {
+    fn spawn<T>(&mut self, components: T) -> Entity
+    where
+        Option<T>: IntoComponentSource,
+    {
+        self.push(components)
+    }
+
+    fn add<C: storage::Component>(&mut self, entity: Entity, component: C) {
+        if let Some(mut entry) = self.entry(entity) {
+            entry.add_component(component);
+        }
+    }
+}
+
+impl SpawnTarget for CommandBuffer {
+    fn spawn<T>(&mut self, components: T) -> Entity
+    where
+        Option<T>: IntoComponentSource,
+    {
+        self.push(components)
+    }
+
+    fn add<C: storage::Component>(&mut self, entity: Entity, component: C) {
+        self.add_component(entity, component);
+    }
+}
+
+pub trait SpawnHierarchy: SpawnTarget + Sized {
+    /// Spawns a root entity with `components`, then its children by calling `children` with a
+    /// builder. Returns the root.
+    fn spawn_hierarchy<T, F>(&mut self, components: T, children: F) -> Entity
+    where
+        Option<T>: IntoComponentSource,
+        F: FnOnce(&mut HierarchyBuilder<Self>),
+    {
+        let root = self.spawn(components);
+        self.add(root, LocalToWorld::identity());
+        HierarchyBuilder::build(self, root, children);
+        root
+    }
+}
+
+impl<S: SpawnTarget> SpawnHierarchy for S {}
+
+/// Spawns the children of a single parent.
+pub struct HierarchyBuilder<'a, S> {
+    target: &'a mut S,
+    parent: Entity,
+    children: SmallVec<[Entity; 8]>,
+}
+
+impl<'a, S: SpawnTarget> HierarchyBuilder<'a, S> {
+    fn build<F: FnOnce(&mut HierarchyBuilder<S>)>(target: &mut S, parent: Entity, children: F) {
+        let mut builder = HierarchyBuilder {
+            target,
+            parent,
+            children: SmallVec::new(),
+        };
+        children(&mut builder);
+        if !builder.children.is_empty() {
+            let children = Children(builder.children);
+            builder.target.add(parent, children);
+        }
+    }
+
+    /// The entity the children are spawned under.
+    pub fn parent(&self) -> Entity {
+        self.parent
+    }
+
+    /// Spawns a child with `components` (and no children of its own).
+    pub fn child<T>(&mut self, components: T) -> &mut Self
+    where
+        Option<T>: IntoComponentSource,
+    {
+        self.with_children(components, |_| {})
+    }
+
+    /// Spawns a child with `components`, then its own children by calling `children` with a
+    /// builder.
+    pub fn with_children<T, F>(&mut self, components: T, children: F) -> &mut Self
+    where
+        Option<T>: IntoComponentSource,
+        F: FnOnce(&mut HierarchyBuilder<S>),
+    {
+        let child = self.target.spawn(components);
+        self.target.add(child, Parent(self.parent));
+        self.target.add(child, PreviousParent(Some(self.parent)));
+        self.target.add(child, LocalToParent::identity());
+        self.target.add(child, LocalToWorld::identity());
+        self.children.push(child);
+
+        HierarchyBuilder::build(&mut *self.target, child, children);
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{hierarchy, missing_previous_parent_system, parent_update_system};
+
+    /// Spawns the same hierarchy through any `SpawnTarget`, returning the root.
+    fn spawn<S: SpawnTarget>(target: &mut S) -> Entity {
+        target.spawn_hierarchy((Translation::new(1.0, 0.0, 0.0),), |parent| {
+            parent
+                .with_children((Translation::new(0.0, 1.0, 0.0),), |parent| {
+                    parent.child((Scale(2.0),));
+                })
+                .child((Rotation::identity(),));
+        })
+    }
+
+    /// Checks that `root` is the root of the hierarchy of `spawn`, with consistent `Parent` and
+    /// `Children`.
+    fn check(world: &World, root: Entity) {
+        let children = hierarchy::children(world, root);
+        assert_eq!(children.len(), 2);
+        let (a, b) = (children[0], children[1]);
+        let c = hierarchy::children(world, a)[0];
+
+        assert_eq!(hierarchy::children(world, a).to_vec(), vec![c]);
+        assert!(hierarchy::children(world, b).is_empty());
+        assert_eq!(hierarchy::parent(world, a), Some(root));
+        assert_eq!(hierarchy::parent(world, b), Some(root));
+        assert_eq!(hierarchy::parent(world, c), Some(a));
+        assert!(world.entry_ref(c).unwrap().get_component::<Scale>().is_ok());
+        assert!(world
+            .entry_ref(c)
+            .unwrap()
+            .get_component::<LocalToParent>()
+            .is_ok());
+    }
+
+    #[test]
+    fn spawns_consistent_hierarchy() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+
+        let mut schedule = Schedule::builder()
+            .add_system(missing_previous_parent_system::build())
+            .flush()
+            .add_system(parent_update_system::build())
+            .build();
+
+        let root = spawn(&mut world);
+        let children = hierarchy::children(&world, root);
+
+        // Consistent right away, and left alone by the bundle.
+        check(&world, root);
+        schedule.execute(&mut world, &mut resources);
+        check(&world, root);
+        assert_eq!(hierarchy::children(&world, root), children);
+    }
+
+    #[test]
+    fn spawns_consistent_hierarchy_deferred() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+
+        let mut schedule = Schedule::builder()
+            .add_system(missing_previous_parent_system::build())
+            .flush()
+            .add_system(parent_update_system::build())
+            .build();
+
+        let mut commands = CommandBuffer::new(&world);
+        let root = spawn(&mut commands);
+        assert!(world.entry(root).is_none());
+
+        // Consistent once flushed, and left alone by the bundle.
+        commands.flush(&mut world);
+        let children = hierarchy::children(&world, root);
+        check(&world, root);
+        schedule.execute(&mut world, &mut resources);
+        check(&world, root);
+        assert_eq!(hierarchy::children(&world, root), children);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{
+    components::*,
+    decompose::decompose,
+    ecs::{systems::ParallelRunnable, *},
+    resources::*,
+};
+
+/// Blends `PreviousLocalToWorld` and `LocalToWorld` into `InterpolatedLocalToWorld` by the
+/// `InterpolationAlpha` resource. The matrices are decomposed so that translation and scale are
+/// linearly interpolated and rotation is spherically interpolated. Requires the
+/// `InterpolationAlpha` resource, and should be scheduled with rendering, after the (flushed)
+/// fixed-step transform system bundle.
+pub fn build() -> impl ParallelRunnable {
+    SystemBuilder::<()>::new("InterpolatedLocalToWorldSystem")
+        .read_resource::<InterpolationAlpha>()
+        .with_query(<(
+            Write<InterpolatedLocalToWorld>,
+            Read<PreviousLocalToWorld>,
+            Read<LocalToWorld>,
+        )>::query())
+        .build(move |_commands, world, alpha, query| {
+            let alpha = alpha.0.max(0.0).min(1.0);
+            query.for_each_mut(
+                world,
+                |(interpolated_local_to_world, previous_local_to_world, local_to_world)| {
+                    let (previous_translation, previous_rotation, previous_scale) =
+                        decompose(&previous_local_to_world.0);
+                    let (translation, rotation, scale) = decompose(&local_to_world.0);
+
+                    let translation = previous_translation.lerp(&translation, alpha);
+                    // Rotations 180 degrees apart have no well defined interpolation.
+                    let rotation = previous_rotation
+                        .try_slerp(&rotation, alpha, 1.0e-6)
+                        .unwrap_or(rotation);
+                    let scale = previous_scale.lerp(&scale, alpha);
+
+                    *interpolated_local_to_world = InterpolatedLocalToWorld(
+                        rotation
+                            .to_homogeneous()
+                            .append_translation(&translation)
+                            .prepend_nonuniform_scaling(&scale),
+                    );
+                },
+            );
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        math::{UnitQuaternion, Vector3},
+        Float,
+    };
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn correct_interpolation() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+        let mut schedule = Schedule::builder().add_system(build()).build();
+
+        resources.insert(InterpolationAlpha(0.5));
+
+        let entity = world.push((
+            PreviousLocalToWorld::identity(),
+            LocalToWorld(
+                Rotation::from_euler_angles(0.0, 0.0, FRAC_PI_2 as Float)
+                    .to_homogeneous()
+                    .append_translation(&Vector3::new(2.0, 0.0, 0.0))
+                    .prepend_scaling(3.0),
+            ),
+            InterpolatedLocalToWorld::identity(),
+        ));
+
+        schedule.execute(&mut world, &mut resources);
+
+        let expected = UnitQuaternion::from_euler_angles(0.0, 0.0, FRAC_PI_2 as Float / 2.0)
+            .to_homogeneous()
+            .append_translation(&Vector3::new(1.0, 0.0, 0.0))
+            .prepend_scaling(2.0);
+        let interpolated = world
+            .entry(entity)
+            .unwrap()
+            .get_component::<InterpolatedLocalToWorld>()
+            .unwrap()
+            .0;
+        assert!((interpolated - expected).norm() < 1e-5);
+    }
+}
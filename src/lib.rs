@@ -1,20 +1,73 @@
 pub use legion as ecs;
 pub use nalgebra as math;
 
+/// The scalar type of every transform component. This is `f32` unless the `f64` feature is
+/// enabled, in which case the whole pipeline runs in double precision.
+#[cfg(not(feature = "f64"))]
+pub type Float = f32;
+#[cfg(feature = "f64")]
+pub type Float = f64;
+
+pub mod billboard_system;
 pub mod components;
+pub mod constraint_system;
+pub mod decompose;
+pub mod despawn;
+pub mod floating_origin_system;
+pub mod hierarchy;
+pub mod hierarchy_builder;
+pub mod interpolated_local_to_world_system;
 pub mod local_to_parent_system;
+pub mod local_to_parent_system_2d;
 pub mod local_to_world_propagate_system;
+pub mod local_to_world_propagate_system_2d;
 pub mod local_to_world_system;
+pub mod local_to_world_system_2d;
+pub mod look_at_system;
 pub mod missing_previous_parent_system;
 pub mod parent_update_system;
+pub mod prefab;
+pub mod previous_local_to_world_system;
+mod propagation;
+pub mod reparent;
+pub mod resources;
+pub mod scene;
+pub mod snapshot;
 pub mod transform_system_bundle;
+pub mod world_space;
+pub mod world_to_local_system;
+pub mod world_transform_system;
+pub mod world_velocity_system;
 
 pub mod prelude {
+    pub use crate::billboard_system;
     pub use crate::components::*;
+    pub use crate::constraint_system;
+    pub use crate::decompose::Decomposition;
+    pub use crate::despawn::*;
+    pub use crate::floating_origin_system;
+    pub use crate::hierarchy;
+    pub use crate::hierarchy_builder::*;
+    pub use crate::interpolated_local_to_world_system;
     pub use crate::local_to_parent_system;
+    pub use crate::local_to_parent_system_2d;
     pub use crate::local_to_world_propagate_system;
+    pub use crate::local_to_world_propagate_system_2d;
     pub use crate::local_to_world_system;
+    pub use crate::local_to_world_system_2d;
+    pub use crate::look_at_system;
     pub use crate::missing_previous_parent_system;
     pub use crate::parent_update_system;
+    pub use crate::prefab::*;
+    pub use crate::previous_local_to_world_system;
+    pub use crate::reparent::*;
+    pub use crate::resources::*;
+    pub use crate::scene::*;
+    pub use crate::snapshot::*;
     pub use crate::transform_system_bundle;
+    pub use crate::world_space::*;
+    pub use crate::world_to_local_system;
+    pub use crate::world_transform_system;
+    pub use crate::world_velocity_system;
+    pub use crate::Float;
 }
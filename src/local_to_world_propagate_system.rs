@@ -1,75 +1,18 @@
-#![allow(dead_code)]
-use crate::{
-    components::*,
-    ecs::{
-        systems::{CommandBuffer, ParallelRunnable},
-        world::SubWorld,
-        *,
-    },
-};
+use crate::{components::*, ecs::systems::ParallelRunnable, propagation};
 
+/// Propagates `LocalToWorld` down the hierarchy, through the `LocalToParent` of every child, only
+/// visiting the subtrees that changed.
 pub fn build() -> impl ParallelRunnable {
-    SystemBuilder::<()>::new("LocalToWorldPropagateSystem")
-        // Entities with a `Children` and `LocalToWorld` but NOT a `Parent` (ie those that are
-        // roots of a hierarchy).
-        .with_query(<(Read<Children>, Read<LocalToWorld>)>::query().filter(!component::<Parent>()))
-        .read_component::<Children>()
-        .read_component::<LocalToParent>()
-        .build(move |commands, world, _resource, query| {
-            for (children, local_to_world) in query.iter(world) {
-                for child in children.0.iter() {
-                    propagate_recursive(*local_to_world, world, *child, commands);
-                }
-            }
-        })
-}
-
-fn propagate_recursive(
-    parent_local_to_world: LocalToWorld,
-    world: &SubWorld,
-    entity: Entity,
-    commands: &mut CommandBuffer,
-) {
-    log::trace!("Updating LocalToWorld for {:?}", entity);
-    let local_to_parent = {
-        if let Some(local_to_parent) = world
-            .entry_ref(entity)
-            .and_then(|entry| entry.into_component::<LocalToParent>().ok())
-        {
-            *local_to_parent
-        } else {
-            log::warn!(
-                "Entity {:?} is a child in the hierarchy but does not have a LocalToParent",
-                entity
-            );
-            return;
-        }
-    };
-
-    let new_local_to_world = LocalToWorld(parent_local_to_world.0 * local_to_parent.0);
-    commands.add_component(entity, new_local_to_world);
-
-    // Collect children
-    let children = if let Some(entry) = world.entry_ref(entity) {
-        entry
-            .get_component::<Children>()
-            .map(|e| e.0.iter().cloned().collect::<Vec<_>>())
-            .unwrap_or_default()
-    } else {
-        Vec::default()
-    };
-
-    for child in children {
-        propagate_recursive(new_local_to_world, world, child, commands);
-    }
+    propagation::build::<LocalToWorld>("LocalToWorldPropagateSystem")
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::{
-        local_to_parent_system, local_to_world_propagate_system, local_to_world_system,
-        missing_previous_parent_system, parent_update_system,
+        ecs::*, local_to_parent_system, local_to_world_propagate_system, local_to_world_system,
+        math::Matrix4, missing_previous_parent_system, parent_update_system,
+        propagation::PARALLEL_CHILDREN_THRESHOLD, Float,
     };
 
     #[test]
@@ -137,4 +80,207 @@ mod test {
                 * Translation::new(0.0, 0.0, 3.0).to_homogeneous()
         );
     }
+
+    #[test]
+    fn skips_unchanged_subtrees() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+
+        let mut schedule = Schedule::builder()
+            .add_system(missing_previous_parent_system::build())
+            .flush()
+            .add_system(parent_update_system::build())
+            .flush()
+            .add_system(local_to_parent_system::build())
+            .flush()
+            .add_system(local_to_world_system::build())
+            .flush()
+            .add_system(local_to_world_propagate_system::build())
+            .build();
+
+        // root
+        //  ├─ e1
+        //  │  └─ e3
+        //  └─ e2
+        let root = world.push((Translation::new(1.0, 0.0, 0.0), LocalToWorld::identity()));
+        let e1 = world.push((
+            Translation::new(0.0, 2.0, 0.0),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            Parent(root),
+        ));
+        // `e2` is given a `Rotation` so that it lives in a different archetype (and so chunk) from
+        // `e1`, as change detection works per-chunk.
+        let e2 = world.push((
+            Translation::new(0.0, 0.0, 3.0),
+            Rotation::identity(),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            Parent(root),
+        ));
+        let e3 = world.push((
+            Translation::new(4.0, 0.0, 0.0),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            Parent(e1),
+        ));
+
+        schedule.execute(&mut world, &mut resources);
+
+        // Scribble over the `LocalToWorld` of `e2`, nothing in the hierarchy moved so it should
+        // not be touched by propagation.
+        let scribble = LocalToWorld(Matrix4::zeros());
+        *world
+            .entry_mut(e2)
+            .unwrap()
+            .get_component_mut::<LocalToWorld>()
+            .unwrap() = scribble;
+        schedule.execute(&mut world, &mut resources);
+        assert_eq!(
+            *world
+                .entry(e2)
+                .unwrap()
+                .get_component::<LocalToWorld>()
+                .unwrap(),
+            scribble
+        );
+
+        // Moving `e1` updates its subtree, but not its sibling.
+        *world
+            .entry_mut(e1)
+            .unwrap()
+            .get_component_mut::<Translation>()
+            .unwrap() = Translation::new(0.0, 5.0, 0.0);
+        schedule.execute(&mut world, &mut resources);
+        assert_eq!(
+            world
+                .entry(e3)
+                .unwrap()
+                .get_component::<LocalToWorld>()
+                .unwrap()
+                .0,
+            Translation::new(1.0, 0.0, 0.0).to_homogeneous()
+                * Translation::new(0.0, 5.0, 0.0).to_homogeneous()
+                * Translation::new(4.0, 0.0, 0.0).to_homogeneous()
+        );
+        assert_eq!(
+            *world
+                .entry(e2)
+                .unwrap()
+                .get_component::<LocalToWorld>()
+                .unwrap(),
+            scribble
+        );
+
+        // Moving the root updates everything.
+        *world
+            .entry_mut(root)
+            .unwrap()
+            .get_component_mut::<Translation>()
+            .unwrap() = Translation::new(2.0, 0.0, 0.0);
+        schedule.execute(&mut world, &mut resources);
+        assert_eq!(
+            world
+                .entry(e2)
+                .unwrap()
+                .get_component::<LocalToWorld>()
+                .unwrap()
+                .0,
+            Translation::new(2.0, 0.0, 0.0).to_homogeneous()
+                * Translation::new(0.0, 0.0, 3.0).to_homogeneous()
+        );
+    }
+
+    #[test]
+    fn propagates_wide_parents_in_parallel() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+
+        let mut schedule = Schedule::builder()
+            .add_system(missing_previous_parent_system::build())
+            .flush()
+            .add_system(parent_update_system::build())
+            .flush()
+            .add_system(local_to_parent_system::build())
+            .flush()
+            .add_system(local_to_world_system::build())
+            .flush()
+            .add_system(local_to_world_propagate_system::build())
+            .build();
+
+        // The wide parent sits below the root, so its children are split up by the recursion
+        // rather than by root.
+        let root = world.push((Translation::new(1.0, 0.0, 0.0), LocalToWorld::identity()));
+        let parent = world.push((
+            Translation::new(0.0, 1.0, 0.0),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            Parent(root),
+        ));
+        let children = (0..PARALLEL_CHILDREN_THRESHOLD * 2)
+            .map(|i| {
+                world.push((
+                    Translation::new(0.0, 0.0, i as Float),
+                    LocalToParent::identity(),
+                    LocalToWorld::identity(),
+                    Parent(parent),
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        schedule.execute(&mut world, &mut resources);
+
+        for (i, child) in children.into_iter().enumerate() {
+            assert_eq!(
+                world
+                    .entry(child)
+                    .unwrap()
+                    .get_component::<LocalToWorld>()
+                    .unwrap()
+                    .0,
+                Translation::new(1.0, 1.0, i as Float).to_homogeneous()
+            );
+        }
+    }
+
+    #[test]
+    fn terminates_on_parent_cycles() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+        let mut schedule = Schedule::builder()
+            .add_system(local_to_world_propagate_system::build())
+            .build();
+
+        // A cycle that `parent_update_system` hasn't had a chance to repair yet.
+        let a = world.push((LocalToParent::identity(), LocalToWorld::identity()));
+        let b = world.push((
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            Parent(a),
+        ));
+        world.entry(a).unwrap().add_component(Parent(b));
+        let c = world.push((
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            Parent(a),
+        ));
+
+        schedule.execute(&mut world, &mut resources);
+
+        // Not reachable from any root, so left alone.
+        assert_eq!(
+            *world
+                .entry(c)
+                .unwrap()
+                .get_component::<LocalToWorld>()
+                .unwrap(),
+            LocalToWorld::identity()
+        );
+    }
 }
@@ -0,0 +1,82 @@
+use crate::{components::*, ecs::systems::ParallelRunnable, propagation};
+
+/// Propagates `LocalToWorld2D` down the hierarchy, through the `LocalToParent2D` of every child, only
+/// visiting the subtrees that changed.
+pub fn build() -> impl ParallelRunnable {
+    propagation::build::<LocalToWorld2D>("LocalToWorldPropagateSystem2D")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        ecs::*, local_to_parent_system_2d, local_to_world_propagate_system_2d,
+        local_to_world_system_2d, missing_previous_parent_system, parent_update_system,
+    };
+
+    #[test]
+    fn did_propagate() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+
+        let mut schedule = Schedule::builder()
+            .add_system(missing_previous_parent_system::build())
+            .flush()
+            .add_system(parent_update_system::build())
+            .flush()
+            .add_system(local_to_parent_system_2d::build())
+            .flush()
+            .add_system(local_to_world_system_2d::build())
+            .flush()
+            .add_system(local_to_world_propagate_system_2d::build())
+            .build();
+
+        // Root entity
+        let parent = world.push((Translation2D::new(1.0, 0.0), LocalToWorld2D::identity()));
+
+        let children = world.extend(vec![
+            (
+                Translation2D::new(0.0, 2.0),
+                LocalToParent2D::identity(),
+                LocalToWorld2D::identity(),
+            ),
+            (
+                Translation2D::new(3.0, 0.0),
+                LocalToParent2D::identity(),
+                LocalToWorld2D::identity(),
+            ),
+        ]);
+        let (e1, e2) = (children[0], children[1]);
+
+        // Parent `e1` and `e2` to `parent`.
+        world.entry(e1).unwrap().add_component(Parent(parent));
+        world.entry(e2).unwrap().add_component(Parent(parent));
+
+        // Run systems
+        schedule.execute(&mut world, &mut resources);
+
+        assert_eq!(
+            world
+                .entry(e1)
+                .unwrap()
+                .get_component::<LocalToWorld2D>()
+                .unwrap()
+                .0,
+            Translation2D::new(1.0, 0.0).to_homogeneous()
+                * Translation2D::new(0.0, 2.0).to_homogeneous()
+        );
+
+        assert_eq!(
+            world
+                .entry(e2)
+                .unwrap()
+                .get_component::<LocalToWorld2D>()
+                .unwrap()
+                .0,
+            Translation2D::new(1.0, 0.0).to_homogeneous()
+                * Translation2D::new(3.0, 0.0).to_homogeneous()
+        );
+    }
+}
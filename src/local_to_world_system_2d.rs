@@ -0,0 +1,227 @@
+#![allow(dead_code)]
+use crate::{
+    components::*,
+    ecs::{systems::ParallelRunnable, *},
+    math::Matrix3,
+};
+
+pub fn build() -> impl ParallelRunnable {
+    SystemBuilder::<()>::new("LocalToWorldUpdateSystem2D")
+        // Translation2D
+        .with_query(
+            <(Write<LocalToWorld2D>, Read<Translation2D>)>::query().filter(
+                !component::<Parent>()
+                    & !component::<Rotation2D>()
+                    & !component::<Scale2D>()
+                    & (maybe_changed::<Translation2D>()),
+            ),
+        )
+        // Rotation2D
+        .with_query(<(Write<LocalToWorld2D>, Read<Rotation2D>)>::query().filter(
+            !component::<Parent>()
+                & !component::<Translation2D>()
+                & !component::<Scale2D>()
+                & (maybe_changed::<Rotation2D>()),
+        ))
+        // Scale2D
+        .with_query(<(Write<LocalToWorld2D>, Read<Scale2D>)>::query().filter(
+            !component::<Parent>()
+                & !component::<Translation2D>()
+                & !component::<Rotation2D>()
+                & (maybe_changed::<Scale2D>()),
+        ))
+        // Translation2D + Rotation2D
+        .with_query(
+            <(Write<LocalToWorld2D>, Read<Translation2D>, Read<Rotation2D>)>::query().filter(
+                !component::<Parent>()
+                    & !component::<Scale2D>()
+                    & (maybe_changed::<Translation2D>() | maybe_changed::<Rotation2D>()),
+            ),
+        )
+        // Translation2D + Scale2D
+        .with_query(
+            <(Write<LocalToWorld2D>, Read<Translation2D>, Read<Scale2D>)>::query().filter(
+                !component::<Parent>()
+                    & !component::<Rotation2D>()
+                    & (maybe_changed::<Translation2D>() | maybe_changed::<Scale2D>()),
+            ),
+        )
+        // Rotation2D + Scale2D
+        .with_query(
+            <(Write<LocalToWorld2D>, Read<Rotation2D>, Read<Scale2D>)>::query().filter(
+                !component::<Parent>()
+                    & !component::<Translation2D>()
+                    & (maybe_changed::<Rotation2D>() | maybe_changed::<Scale2D>()),
+            ),
+        )
+        // Translation2D + Rotation2D + Scale2D
+        .with_query(
+            <(
+                Write<LocalToWorld2D>,
+                Read<Translation2D>,
+                Read<Rotation2D>,
+                Read<Scale2D>,
+            )>::query()
+            .filter(
+                !component::<Parent>()
+                    & (maybe_changed::<Translation2D>()
+                        | maybe_changed::<Rotation2D>()
+                        | maybe_changed::<Scale2D>()),
+            ),
+        )
+        .build(move |_commands, world, _, queries| {
+            let (a, b, c, d, e, f, g) = queries;
+            rayon::scope(|s| {
+                s.spawn(|_| unsafe {
+                    // Translation2D
+                    a.for_each_unchecked(world, |(ltw, translation)| {
+                        *ltw = LocalToWorld2D(translation.to_homogeneous());
+                    });
+                });
+                s.spawn(|_| unsafe {
+                    // Rotation2D
+                    b.for_each_unchecked(world, |(ltw, rotation)| {
+                        *ltw = LocalToWorld2D(rotation.to_homogeneous());
+                    });
+                });
+                s.spawn(|_| unsafe {
+                    // Scale2D
+                    c.for_each_unchecked(world, |(ltw, scale)| {
+                        *ltw = LocalToWorld2D(Matrix3::new_scaling(scale.0));
+                    });
+                });
+                s.spawn(|_| unsafe {
+                    // Translation2D + Rotation2D
+                    d.for_each_unchecked(world, |(ltw, translation, rotation)| {
+                        *ltw = LocalToWorld2D(
+                            rotation
+                                .to_homogeneous()
+                                .append_translation(&translation.vector),
+                        );
+                    });
+                });
+                s.spawn(|_| unsafe {
+                    // Translation2D + Scale2D
+                    e.for_each_unchecked(world, |(ltw, translation, scale)| {
+                        *ltw =
+                            LocalToWorld2D(translation.to_homogeneous().prepend_scaling(scale.0));
+                    });
+                });
+                s.spawn(|_| unsafe {
+                    // Rotation2D + Scale2D
+                    f.for_each_unchecked(world, |(ltw, rotation, scale)| {
+                        *ltw = LocalToWorld2D(rotation.to_homogeneous().prepend_scaling(scale.0));
+                    });
+                });
+                s.spawn(|_| unsafe {
+                    // Translation2D + Rotation2D + Scale2D
+                    g.for_each_unchecked(world, |(ltw, translation, rotation, scale)| {
+                        *ltw = LocalToWorld2D(
+                            rotation
+                                .to_homogeneous()
+                                .append_translation(&translation.vector)
+                                .prepend_scaling(scale.0),
+                        );
+                    });
+                });
+            });
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn correct_world_transformation() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+        let mut schedule = Schedule::builder().add_system(build()).build();
+
+        let ltw = LocalToWorld2D::identity();
+        let t = Translation2D::new(1.0, 2.0);
+        let r = Rotation2D::from_angle(1.0);
+        let s = Scale2D(2.0);
+
+        // Add every combination of transform types.
+        let translation = world.push((ltw, t));
+        let rotation = world.push((ltw, r));
+        let scale = world.push((ltw, s));
+        let translation_and_rotation = world.push((ltw, t, r));
+        let translation_and_scale = world.push((ltw, t, s));
+        let rotation_scale = world.push((ltw, r, s));
+        let translation_rotation_scale = world.push((ltw, t, r, s));
+
+        // Run the system
+        schedule.execute(&mut world, &mut resources);
+
+        // Verify that each was transformed correctly.
+        assert_eq!(
+            world
+                .entry(translation)
+                .unwrap()
+                .get_component::<LocalToWorld2D>()
+                .unwrap()
+                .0,
+            t.to_homogeneous()
+        );
+        assert_eq!(
+            world
+                .entry(rotation)
+                .unwrap()
+                .get_component::<LocalToWorld2D>()
+                .unwrap()
+                .0,
+            r.to_homogeneous()
+        );
+        assert_eq!(
+            world
+                .entry(scale)
+                .unwrap()
+                .get_component::<LocalToWorld2D>()
+                .unwrap()
+                .0,
+            Matrix3::new_scaling(s.0),
+        );
+        assert_eq!(
+            world
+                .entry(translation_and_rotation)
+                .unwrap()
+                .get_component::<LocalToWorld2D>()
+                .unwrap()
+                .0,
+            r.to_homogeneous().append_translation(&t.vector),
+        );
+        assert_eq!(
+            world
+                .entry(translation_and_scale)
+                .unwrap()
+                .get_component::<LocalToWorld2D>()
+                .unwrap()
+                .0,
+            t.to_homogeneous().prepend_scaling(s.0),
+        );
+        assert_eq!(
+            world
+                .entry(rotation_scale)
+                .unwrap()
+                .get_component::<LocalToWorld2D>()
+                .unwrap()
+                .0,
+            r.to_homogeneous().prepend_scaling(s.0)
+        );
+        assert_eq!(
+            world
+                .entry(translation_rotation_scale)
+                .unwrap()
+                .get_component::<LocalToWorld2D>()
+                .unwrap()
+                .0,
+            r.to_homogeneous()
+                .append_translation(&t.vector)
+                .prepend_scaling(s.0)
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{
+    components::*,
+    ecs::{systems::ParallelRunnable, world::SubWorld, *},
+    hierarchy,
+    math::{Matrix4, Point3, UnitQuaternion, Vector3},
+    Float,
+};
+
+/// Points the `Rotation` of entities with a `LookAt` at their target. The rotation is computed in
+/// the entity's parent space, from the `LocalToWorld` of the parent and of the target entity.
+/// Part of the transform system bundle, before `LocalToParent` and `LocalToWorld` are computed,
+/// so those `LocalToWorld`s are the ones from the previous run.
+pub fn build() -> impl ParallelRunnable {
+    SystemBuilder::<()>::new("LookAtSystem")
+        .with_query(<(Entity, Read<LookAt>)>::query().filter(component::<Rotation>()))
+        .read_component::<Translation>()
+        .read_component::<Parent>()
+        .read_component::<LocalToWorld>()
+        .write_component::<Rotation>()
+        .build(move |_commands, world, _resource, query| {
+            // Compute every rotation first, as an entity can be the target of another.
+            let rotations = query
+                .iter(world)
+                .filter_map(|(entity, look_at)| {
+                    aim(world, *entity, look_at).map(|rotation| (*entity, rotation))
+                })
+                .collect::<Vec<_>>();
+
+            for (entity, rotation) in rotations {
+                if let Some(current) = world
+                    .entry_mut(entity)
+                    .and_then(|entry| entry.into_component_mut::<Rotation>().ok())
+                {
+                    *current = Rotation(rotation);
+                }
+            }
+        })
+}
+
+/// The parent-space rotation that aims `entity` at the target of `look_at`.
+fn aim(world: &SubWorld, entity: Entity, look_at: &LookAt) -> Option<UnitQuaternion<Float>> {
+    let local_to_world = |entity: Entity| {
+        world
+            .entry_ref(entity)
+            .and_then(|entry| entry.into_component::<LocalToWorld>().ok())
+            .map(|local_to_world| local_to_world.0)
+    };
+    let position =
+        |matrix: &Matrix4<Float>| Vector3::new(matrix[(0, 3)], matrix[(1, 3)], matrix[(2, 3)]);
+
+    let target = match look_at.target {
+        LookAtTarget::Entity(target) => match local_to_world(target) {
+            Some(local_to_world) => position(&local_to_world),
+            None => {
+                log::warn!(
+                    "The LookAt target {:?} of {:?} has no LocalToWorld",
+                    target,
+                    entity
+                );
+                return None;
+            }
+        },
+        LookAtTarget::Point(point) => point,
+    };
+
+    let parent_to_world = hierarchy::parent(world, entity)
+        .and_then(local_to_world)
+        .unwrap_or_else(Matrix4::identity);
+    let world_to_parent = parent_to_world.try_inverse()?;
+
+    // The current translation (the `LocalToWorld` of `entity` isn't computed yet this run).
+    let translation = world
+        .entry_ref(entity)
+        .and_then(|entry| entry.into_component::<Translation>().ok())
+        .map(|translation| translation.vector)
+        .unwrap_or_else(Vector3::zeros);
+    let origin = parent_to_world
+        .transform_point(&Point3::from(translation))
+        .coords;
+
+    let direction = world_to_parent.transform_vector(&(target - origin));
+    let up = world_to_parent.transform_vector(&look_at.up);
+    if direction.norm() < 1.0e-6 || direction.cross(&up).norm() < 1.0e-6 * direction.norm() {
+        // No (or no well defined) direction to face.
+        return None;
+    }
+
+    let mut rotation = UnitQuaternion::face_towards(&direction, &up) * look_at.axis.to_positive_z();
+    if let Some(max_angle) = look_at.max_angle {
+        let angle = rotation.angle();
+        if angle > max_angle {
+            rotation =
+                UnitQuaternion::from_scaled_axis(rotation.scaled_axis() * (max_angle / angle));
+        }
+    }
+    let weight = look_at.weight.max(0.0).min(1.0);
+    Some(UnitQuaternion::from_scaled_axis(
+        rotation.scaled_axis() * weight,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn aims_at_target() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+        let mut schedule = Schedule::builder().add_system(build()).build();
+
+        let target = world.push((LocalToWorld(Matrix4::new_translation(&Vector3::new(
+            1.0, 0.0, 0.0,
+        ))),));
+        let at_entity = world.push((
+            Rotation::identity(),
+            LookAt::new(LookAtTarget::Entity(target)),
+        ));
+        let at_point = world.push((
+            Translation::new(0.0, 0.0, 1.0),
+            Rotation::identity(),
+            LookAt {
+                axis: AimAxis::NegativeZ,
+                ..LookAt::new(LookAtTarget::Point(Vector3::new(0.0, 0.0, 0.0)))
+            },
+        ));
+        let halfway = world.push((
+            Rotation::identity(),
+            LookAt {
+                weight: 0.5,
+                ..LookAt::new(LookAtTarget::Entity(target))
+            },
+        ));
+        let clamped = world.push((
+            Rotation::identity(),
+            LookAt {
+                max_angle: Some(0.1),
+                ..LookAt::new(LookAtTarget::Entity(target))
+            },
+        ));
+
+        // A rotated parent, the child's rotation is in its space.
+        let parent = world.push((LocalToWorld(
+            UnitQuaternion::from_euler_angles(0.0, 0.0, FRAC_PI_2 as Float).to_homogeneous(),
+        ),));
+        let child = world.push((
+            Rotation::identity(),
+            Parent(parent),
+            LookAt {
+                up: Vector3::z(),
+                ..LookAt::new(LookAtTarget::Point(Vector3::new(0.0, 1.0, 0.0)))
+            },
+        ));
+
+        schedule.execute(&mut world, &mut resources);
+
+        let rotation = |entity| {
+            world
+                .entry_ref(entity)
+                .unwrap()
+                .get_component::<Rotation>()
+                .unwrap()
+                .0
+        };
+        assert!((rotation(at_entity) * Vector3::z() - Vector3::x()).norm() < 1e-5);
+        assert!((rotation(at_point) * -Vector3::z() - -Vector3::z()).norm() < 1e-5);
+        assert!((rotation(halfway).angle() - FRAC_PI_2 as Float / 2.0).abs() < 1e-5);
+        assert!((rotation(clamped).angle() - 0.1).abs() < 1e-5);
+        // World +Y is the parent's local +X.
+        assert!((rotation(child) * Vector3::z() - Vector3::x()).norm() < 1e-5);
+    }
+}
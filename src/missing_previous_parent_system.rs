@@ -7,8 +7,8 @@ pub fn build() -> impl ParallelRunnable {
     SystemBuilder::<()>::new("MissingPreviousParentSystem")
         // Entities with missing `PreviousParent`
         .with_query(<(Entity, Read<Parent>)>::query().filter(
-            component::<LocalToParent>()
-                & component::<LocalToWorld>()
+            (component::<LocalToParent>() | component::<LocalToParent2D>())
+                & (component::<LocalToWorld>() | component::<LocalToWorld2D>())
                 & !component::<PreviousParent>(),
         ))
         .build(move |commands, world, _resource, query| {
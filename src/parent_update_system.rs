@@ -1,131 +1,429 @@
 #![allow(dead_code)]
 use crate::{
     components::*,
-    ecs::{systems::ParallelRunnable, *},
+    decompose::{bake_trs, bake_trs_2d},
+    despawn,
+    ecs::{systems::ParallelRunnable, world::SubWorld, *},
+    hierarchy,
+    math::{Matrix3, Matrix4},
+    resources::*,
 };
 use smallvec::SmallVec;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-pub fn build() -> impl ParallelRunnable {
-    SystemBuilder::<()>::new("ParentUpdateSystem")
-        // Entities with a removed `Parent`
-        .with_query(<(Entity, Read<PreviousParent>)>::query().filter(!component::<Parent>()))
-        // Entities with a changed `Parent`
-        .with_query(
-            <(Entity, Read<Parent>, Write<PreviousParent>)>::query().filter(
-                component::<LocalToParent>()
-                    & component::<LocalToWorld>()
-                    & maybe_changed::<Parent>(),
-            ),
-        )
-        // Deleted Parents (ie Entities with `Children` and without a `LocalToWorld`).
-        .with_query(<(Entity, Read<Children>)>::query().filter(!component::<LocalToWorld>()))
-        .write_component::<Children>()
-        .build(move |commands, world, _resource, queries| {
-            // Entities with a missing `Parent` (ie. ones that have a `PreviousParent`), remove
-            // them from the `Children` of the `PreviousParent`.
-            let (ref mut left, ref mut right) = world.split::<Write<Children>>();
-            for (entity, previous_parent) in queries.0.iter(right) {
-                log::trace!("Parent was removed from {:?}", entity);
-                if let Some(previous_parent_entity) = previous_parent.0 {
-                    if let Some(previous_parent_children) = left
-                        .entry_mut(previous_parent_entity)
-                        .and_then(|entry| entry.into_component_mut::<Children>().ok())
-                    {
-                        log::trace!(" > Removing {:?} from it's prev parent's children", entity);
-                        previous_parent_children.0.retain(|e| e != entity);
+/// Adds the queries and the run function of the parent update system to `$builder`, which
+/// declares the resources. The `HierarchyEvent`s found by a run are collected in `$found`, then
+/// handed to `$publish` along with the resources.
+macro_rules! parent_update_system {
+    ($builder:expr, $policy:ident, |$resources:pat, $found:ident| $publish:expr) => {
+        $builder
+            // Entities with a removed `Parent`
+            .with_query(<(Entity, Read<PreviousParent>)>::query().filter(!component::<Parent>()))
+            // Entities with a changed `Parent`
+            .with_query(
+                <(Entity, Read<Parent>, Write<PreviousParent>)>::query().filter(
+                    (component::<LocalToParent>() | component::<LocalToParent2D>())
+                        & (component::<LocalToWorld>() | component::<LocalToWorld2D>())
+                        & maybe_changed::<Parent>(),
+                ),
+            )
+            // Deleted Parents (ie Entities with `Children` and without a `LocalToWorld` or
+            // `LocalToWorld2D`).
+            .with_query(
+                <(Entity, Read<Children>)>::query()
+                    .filter(!component::<LocalToWorld>() & !component::<LocalToWorld2D>()),
+            )
+            // Entities with a changed `Parent`, read-only to check for cycles.
+            .with_query(
+                <(Entity, Read<Parent>, Read<PreviousParent>)>::query().filter(
+                    (component::<LocalToParent>() | component::<LocalToParent2D>())
+                        & (component::<LocalToWorld>() | component::<LocalToWorld2D>())
+                        & maybe_changed::<Parent>(),
+                ),
+            )
+            .read_component::<Parent>()
+            .read_component::<LocalToWorld>()
+            .read_component::<LocalToWorld2D>()
+            .write_component::<Children>()
+            .build(move |commands, world, $resources, queries| {
+                let mut $found = Vec::new();
+
+                // Links that would form a cycle, with the `Parent` they are repaired to (if any).
+                let mut repaired = HashMap::<Entity, Option<Entity>>::new();
+
+                // Entities with a missing `Parent` (ie. ones that have a `PreviousParent`), remove
+                // them from the `Children` of the `PreviousParent`.
+                let (ref mut left, ref mut right) = world.split::<Write<Children>>();
+                for (entity, previous_parent) in queries.0.iter(right) {
+                    log::trace!("Parent was removed from {:?}", entity);
+                    if let Some(previous_parent_entity) = previous_parent.0 {
+                        if right.entry_ref(previous_parent_entity).is_none() {
+                            log::trace!(
+                                " > And the prev parent was deleted, removing PreviousParent"
+                            );
+                            commands.remove_component::<PreviousParent>(*entity);
+                            continue;
+                        }
+                        if let Some(previous_parent_children) = left
+                            .entry_mut(previous_parent_entity)
+                            .and_then(|entry| entry.into_component_mut::<Children>().ok())
+                        {
+                            log::trace!(
+                                " > Removing {:?} from it's prev parent's children",
+                                entity
+                            );
+                            previous_parent_children.0.retain(|e| e != entity);
+                        }
                     }
                 }
-            }
 
-            // Tracks all newly created `Children` Components this frame.
-            let mut children_additions =
-                HashMap::<Entity, SmallVec<[Entity; 8]>>::with_capacity(16);
+                // Refuse (or break) any new `Parent` that would make an entity its own ancestor.
+                let changed_parents = queries
+                    .3
+                    .iter(right)
+                    .filter(|(_, parent, previous_parent)| previous_parent.0 != Some(parent.0))
+                    .map(|(entity, parent, previous_parent)| (*entity, parent.0, previous_parent.0))
+                    .collect::<Vec<_>>();
+                // Restoring a refused link can close a cycle through a link that was accepted
+                // before it, so check again until nothing else is repaired.
+                loop {
+                    let repaired_count = repaired.len();
+                    for &(entity, parent, previous_parent) in &changed_parents {
+                        if repaired.contains_key(&entity) {
+                            continue;
+                        }
+                        if let Some(cycle) = find_cycle(right, entity, parent, &repaired) {
+                            log::warn!(
+                                "Parenting {:?} to {:?} would form the cycle {:?}",
+                                entity,
+                                parent,
+                                cycle
+                            );
+                            let restored = match ($policy.cycle, previous_parent) {
+                                (CyclePolicy::Refuse, Some(previous_parent)) => {
+                                    commands.add_component(entity, Parent(previous_parent));
+                                    Some(previous_parent)
+                                }
+                                _ => {
+                                    commands.remove_component::<Parent>(entity);
+                                    None
+                                }
+                            };
+                            repaired.insert(entity, restored);
+                            $found.push(HierarchyEvent::CycleDetected {
+                                entity,
+                                parent,
+                                cycle,
+                            });
+                        }
+                    }
+                    if repaired.len() == repaired_count {
+                        break;
+                    }
+                }
+
+                // Tracks the new children of each parent this frame.
+                let mut new_children = HashMap::<Entity, SmallVec<[Entity; 8]>>::with_capacity(16);
 
-            // Entities with a changed Parent (that also have a PreviousParent, even if None)
-            for (entity, parent, previous_parent) in queries.1.iter_mut(right) {
-                log::trace!("Parent changed for {:?}", entity);
+                // Entities with a changed Parent (that also have a PreviousParent, even if None)
+                for (entity, parent, previous_parent) in queries.1.iter_mut(right) {
+                    log::trace!("Parent changed for {:?}", entity);
 
-                // If the `PreviousParent` is not None.
-                if let Some(previous_parent_entity) = previous_parent.0 {
-                    // New and previous point to the same Entity, carry on, nothing to see here.
-                    if previous_parent_entity == parent.0 {
-                        log::trace!(" > But the previous parent is the same, ignoring...");
+                    if repaired.contains_key(entity) {
+                        log::trace!(" > But it would form a cycle, ignoring...");
                         continue;
                     }
 
-                    // Remove from `PreviousParent.Children`.
-                    if let Some(previous_parent_children) = left
-                        .entry_mut(previous_parent_entity)
-                        .and_then(|entry| entry.into_component_mut::<Children>().ok())
-                    {
-                        log::trace!(" > Removing {:?} from prev parent's children", entity);
-                        previous_parent_children.0.retain(|e| e != entity);
+                    // If the `PreviousParent` is not None.
+                    if let Some(previous_parent_entity) = previous_parent.0 {
+                        // New and previous point to the same Entity, carry on, nothing to see here.
+                        if previous_parent_entity == parent.0 {
+                            log::trace!(" > But the previous parent is the same, ignoring...");
+                            continue;
+                        }
+
+                        // Remove from `PreviousParent.Children`.
+                        if let Some(previous_parent_children) = left
+                            .entry_mut(previous_parent_entity)
+                            .and_then(|entry| entry.into_component_mut::<Children>().ok())
+                        {
+                            log::trace!(" > Removing {:?} from prev parent's children", entity);
+                            previous_parent_children.0.retain(|e| e != entity);
+                        }
                     }
-                }
 
-                // Set `PreviousParent = Parent`.
-                *previous_parent = PreviousParent(Some(parent.0));
+                    // Set `PreviousParent = Parent`.
+                    *previous_parent = PreviousParent(Some(parent.0));
 
-                // Add to the parent's `Children` (either the real component, or
-                // `children_additions`).
-                log::trace!("Adding {:?} to it's new parent {:?}", entity, parent.0);
-                if let Some(new_parent_children) = left
-                    .entry_mut(parent.0)
-                    .and_then(|entry| entry.into_component_mut::<Children>().ok())
-                {
-                    // This is the parent
-                    log::trace!(
-                        " > The new parent {:?} already has a `Children`, adding to it.",
-                        parent.0
-                    );
-                    new_parent_children.0.push(*entity);
-                } else {
-                    // The parent doesn't have a children entity, lets add it
-                    log::trace!(
-                        "The new parent {:?} doesn't yet have `Children` component.",
-                        parent.0
-                    );
-                    children_additions
+                    log::trace!("Adding {:?} to it's new parent {:?}", entity, parent.0);
+                    new_children
                         .entry(parent.0)
                         .or_insert_with(Default::default)
                         .push(*entity);
                 }
-            }
 
-            // Deleted `Parents` (ie. Entities with a `Children` but no `LocalToWorld`).
-            for (entity, children) in queries.2.iter(world) {
-                log::trace!("The entity {:?} doesn't have a LocalToWorld", entity);
-                if children_additions.remove(&entity).is_none() {
-                    log::trace!(" > It needs to be remove from the ECS.");
-                    for child_entity in children.0.iter() {
-                        commands.remove_component::<Parent>(*child_entity);
-                        commands.remove_component::<PreviousParent>(*child_entity);
-                        commands.remove_component::<LocalToParent>(*child_entity);
+                // Tracks all newly created `Children` Components this frame.
+                let mut children_additions =
+                    HashMap::<Entity, SmallVec<[Entity; 8]>>::with_capacity(16);
+
+                // Append the new children sorted by `Entity` (ie. roughly in creation order), so
+                // the order of `Children` doesn't depend on the order the query happened to visit
+                // them in.
+                for (parent, mut entities) in new_children {
+                    entities.sort();
+                    if let Some(new_parent_children) = left
+                        .entry_mut(parent)
+                        .and_then(|entry| entry.into_component_mut::<Children>().ok())
+                    {
+                        log::trace!(
+                            " > The new parent {:?} already has a `Children`, adding to it.",
+                            parent
+                        );
+                        new_parent_children.0.extend(entities);
+                    } else {
+                        log::trace!(
+                            "The new parent {:?} doesn't yet have `Children` component.",
+                            parent
+                        );
+                        children_additions.insert(parent, entities);
                     }
-                    commands.remove_component::<Children>(*entity);
-                } else {
-                    log::trace!(" > It was a new addition, removing it from additions map");
                 }
+
+                // Deleted `Parents` (ie. Entities with a `Children` but no `LocalToWorld`).
+                let deleted_parents = queries
+                    .2
+                    .iter(world)
+                    .map(|(entity, children)| (*entity, children.0.clone()))
+                    .collect::<Vec<_>>();
+                for (entity, children) in deleted_parents {
+                    log::trace!("The entity {:?} doesn't have a LocalToWorld", entity);
+                    if children_additions.remove(&entity).is_none() {
+                        log::trace!(" > It needs to be remove from the ECS.");
+                        for child_entity in children {
+                            // The child may have been moved to another parent since.
+                            if hierarchy::parent(world, child_entity) == Some(entity) {
+                                handle_orphan(
+                                    world,
+                                    commands,
+                                    $policy.orphan,
+                                    entity,
+                                    child_entity,
+                                );
+                            }
+                        }
+                        commands.remove_component::<Children>(entity);
+                    } else {
+                        log::trace!(" > It was a new addition, removing it from additions map");
+                    }
+                }
+
+                // Flush the `children_additions` to the command buffer. It is stored separate to
+                // collect multiple new children that point to the same parent into the same
+                // SmallVec, and to prevent redundant add+remove operations.
+                children_additions.iter().for_each(|(k, v)| {
+                    log::trace!(
+                        "Flushing: Entity {:?} adding `Children` component {:?}",
+                        k,
+                        v
+                    );
+                    commands.add_component(*k, Children::with(v));
+                });
+
+                $publish;
+            })
+    };
+}
+
+/// Maintains `Children` and `PreviousParent` from the `Parent` components, repairing the hierarchy
+/// according to the default `HierarchyPolicy`.
+pub fn build() -> impl ParallelRunnable {
+    build_with_policy(HierarchyPolicy::default())
+}
+
+/// Like `build`, repairing the hierarchy according to `policy`.
+pub fn build_with_policy(policy: HierarchyPolicy) -> impl ParallelRunnable {
+    parent_update_system!(
+        SystemBuilder::<()>::new("ParentUpdateSystem"),
+        policy,
+        |_, _events| {}
+    )
+}
+
+/// Like `build_with_policy`, also reporting the problems found by each run in the
+/// `HierarchyEvents` resource, which must be inserted.
+pub fn build_with_events(policy: HierarchyPolicy) -> impl ParallelRunnable {
+    parent_update_system!(
+        SystemBuilder::<()>::new("ParentUpdateSystem").write_resource::<HierarchyEvents>(),
+        policy,
+        |events, found| events.0 = found
+    )
+}
+
+/// Repairs the hierarchy after entities were deleted from the world outright (eg. with
+/// `World::remove`, rather than `despawn::despawn_recursive`): children of a deleted entity are
+/// handled according to `policy.orphan`, and deleted entities are removed from `Children`.
+///
+/// Finding them means visiting every entity with a `Parent` or `Children`, so this isn't part of
+/// the transform system bundle. Run it (followed by a flush) before the bundle on frames where
+/// entities in a hierarchy may have been deleted.
+pub fn build_cleanup(policy: HierarchyPolicy) -> impl ParallelRunnable {
+    SystemBuilder::<()>::new("ParentCleanupSystem")
+        // All children, to find the ones whose `Parent` was deleted from the world.
+        .with_query(<(Entity, Read<Parent>)>::query())
+        // All parents, to find deleted entities in their `Children`.
+        .with_query(<(Entity, Read<Children>)>::query())
+        .read_component::<Parent>()
+        .read_component::<LocalToWorld>()
+        .read_component::<LocalToWorld2D>()
+        .write_component::<Children>()
+        .build(move |commands, world, _resources, queries| {
+            // Entities whose `Parent` was deleted outright are orphans, just like the children of
+            // a parent that lost its `LocalToWorld`.
+            let dangling_parents = queries
+                .0
+                .iter(world)
+                .filter(|(_, parent)| world.entry_ref(parent.0).is_none())
+                .map(|(entity, parent)| (*entity, parent.0))
+                .collect::<Vec<_>>();
+            for (entity, parent) in dangling_parents {
+                log::trace!("The parent {:?} of {:?} was deleted", parent, entity);
+                handle_orphan(world, commands, policy.orphan, parent, entity);
             }
 
-            // Flush the `children_additions` to the command buffer. It is stored separate to
-            // collect multiple new children that point to the same parent into the same
-            // SmallVec, and to prevent redundant add+remove operations.
-            children_additions.iter().for_each(|(k, v)| {
+            // Remove deleted entities from `Children`.
+            let dangling_children = queries
+                .1
+                .iter(world)
+                .filter(|(_, children)| {
+                    children
+                        .0
+                        .iter()
+                        .any(|child| world.entry_ref(*child).is_none())
+                })
+                .map(|(entity, children)| {
+                    let alive = children
+                        .0
+                        .iter()
+                        .cloned()
+                        .filter(|child| world.entry_ref(*child).is_some())
+                        .collect::<SmallVec<[Entity; 8]>>();
+                    (*entity, alive)
+                })
+                .collect::<Vec<_>>();
+            for (entity, alive) in dangling_children {
                 log::trace!(
-                    "Flushing: Entity {:?} adding `Children` component {:?}",
-                    k,
-                    v
+                    "Removing deleted entities from the children of {:?}",
+                    entity
                 );
-                commands.add_component(*k, Children::with(v));
-            });
+                if let Some(children) = world
+                    .entry_mut(entity)
+                    .and_then(|entry| entry.into_component_mut::<Children>().ok())
+                {
+                    children.0 = alive;
+                }
+            }
         })
 }
 
+/// Applies the `OrphanPolicy` to `child`, whose parent `parent` was deleted.
+fn handle_orphan(
+    world: &SubWorld,
+    commands: &mut CommandBuffer,
+    policy: OrphanPolicy,
+    parent: Entity,
+    child: Entity,
+) {
+    log::trace!(" > Applying {:?} to the orphan {:?}", policy, child);
+
+    let grandparent = match policy {
+        OrphanPolicy::Delete => {
+            for entity in despawn::subtree(world, child) {
+                commands.remove(entity);
+            }
+            return;
+        }
+        OrphanPolicy::ReparentToGrandparent => {
+            if world.entry_ref(parent).is_none() {
+                log::warn!(
+                    "The parent {:?} of {:?} was removed from the world, so its own parent is \
+                     unknown, detaching {:?} instead",
+                    parent,
+                    child,
+                    child
+                );
+            }
+            hierarchy::parent(world, parent)
+        }
+        OrphanPolicy::Detach | OrphanPolicy::DetachKeepWorld => None,
+    };
+
+    if policy != OrphanPolicy::Detach {
+        if let Some(local_to_world) = get_copied::<LocalToWorld>(world, child) {
+            let world_to_grandparent = grandparent
+                .and_then(|grandparent| get_copied::<LocalToWorld>(world, grandparent))
+                .and_then(|local_to_world| local_to_world.try_inverse())
+                .unwrap_or_else(Matrix4::identity);
+            bake_trs(commands, child, &(world_to_grandparent * local_to_world.0));
+        } else if let Some(local_to_world) = get_copied::<LocalToWorld2D>(world, child) {
+            let world_to_grandparent = grandparent
+                .and_then(|grandparent| get_copied::<LocalToWorld2D>(world, grandparent))
+                .and_then(|local_to_world| local_to_world.try_inverse())
+                .unwrap_or_else(Matrix3::identity);
+            bake_trs_2d(commands, child, &(world_to_grandparent * local_to_world.0));
+        }
+    }
+
+    match grandparent {
+        // `PreviousParent` is left pointing at the deleted parent, so the move is picked up as a
+        // changed `Parent` next run.
+        Some(grandparent) => commands.add_component(child, Parent(grandparent)),
+        None => {
+            commands.remove_component::<Parent>(child);
+            commands.remove_component::<PreviousParent>(child);
+            commands.remove_component::<LocalToParent>(child);
+            commands.remove_component::<LocalToParent2D>(child);
+        }
+    }
+}
+
+/// A copy of the `T` of `entity`, if it has one.
+fn get_copied<T: storage::Component + Copy>(world: &SubWorld, entity: Entity) -> Option<T> {
+    world
+        .entry_ref(entity)
+        .and_then(|entry| entry.into_component::<T>().ok())
+        .copied()
+}
+
+/// Follows `Parent` up from `parent`, returning the entities walked through if `entity` is reached,
+/// ie. if parenting `entity` to `parent` forms a cycle. Links in `repaired` are followed to the
+/// `Parent` they are repaired to.
+fn find_cycle(
+    world: &SubWorld,
+    entity: Entity,
+    parent: Entity,
+    repaired: &HashMap<Entity, Option<Entity>>,
+) -> Option<Vec<Entity>> {
+    let mut cycle = vec![entity];
+    let mut visited = HashSet::new();
+    let mut current = parent;
+    while current != entity {
+        // Stop at an existing cycle that `entity` isn't in.
+        if !visited.insert(current) {
+            return None;
+        }
+        cycle.push(current);
+        current = match repaired.get(&current) {
+            Some(repaired) => (*repaired)?,
+            None => hierarchy::parent(world, current)?,
+        };
+    }
+    Some(cycle)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::math::Vector3;
     use crate::missing_previous_parent_system;
 
     #[test]
@@ -231,4 +529,355 @@ mod test {
             vec![e2]
         );
     }
+
+    #[test]
+    fn rejects_cycles() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        resources.insert(HierarchyEvents::default());
+        let mut world = World::default();
+
+        let mut schedule = Schedule::builder()
+            .add_system(missing_previous_parent_system::build())
+            .flush()
+            .add_system(build_with_events(HierarchyPolicy::default()))
+            .build();
+
+        let a = world.push((
+            Translation::identity(),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+        ));
+        let b = world.push((
+            Translation::identity(),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            Parent(a),
+        ));
+
+        schedule.execute(&mut world, &mut resources);
+        assert!(resources.get::<HierarchyEvents>().unwrap().0.is_empty());
+
+        // Parent `a` to its own child.
+        world.entry(a).unwrap().add_component(Parent(b));
+
+        schedule.execute(&mut world, &mut resources);
+
+        assert_eq!(
+            resources.get::<HierarchyEvents>().unwrap().0,
+            vec![HierarchyEvent::CycleDetected {
+                entity: a,
+                parent: b,
+                cycle: vec![a, b],
+            }]
+        );
+
+        // `a` had no previous parent, so refusing the change removes its `Parent`.
+        assert!(world.entry(a).unwrap().get_component::<Parent>().is_err());
+        assert!(world.entry(b).unwrap().get_component::<Children>().is_err());
+        assert_eq!(
+            world
+                .entry(a)
+                .unwrap()
+                .get_component::<Children>()
+                .unwrap()
+                .0
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![b]
+        );
+    }
+
+    #[test]
+    fn rejects_cycles_through_refused_links() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        resources.insert(HierarchyEvents::default());
+        let mut world = World::default();
+
+        let mut schedule = Schedule::builder()
+            .add_system(missing_previous_parent_system::build())
+            .flush()
+            .add_system(build_with_events(HierarchyPolicy::default()))
+            .build();
+
+        // p
+        //  └─ a
+        //      └─ b
+        let p = world.push((
+            Translation::identity(),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+        ));
+        let a = world.push((
+            Translation::identity(),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            Parent(p),
+        ));
+        let b = world.push((
+            Translation::identity(),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            Parent(a),
+        ));
+
+        schedule.execute(&mut world, &mut resources);
+
+        // In the same run, parent `a` to its own child, and `p` to `a`. Refusing the first
+        // restores `a` to `p`, which makes the second a cycle too.
+        world.entry(a).unwrap().add_component(Parent(b));
+        world.entry(p).unwrap().add_component(Parent(a));
+
+        schedule.execute(&mut world, &mut resources);
+
+        assert_eq!(resources.get::<HierarchyEvents>().unwrap().0.len(), 2);
+        assert_eq!(
+            world.entry(a).unwrap().get_component::<Parent>().unwrap().0,
+            p
+        );
+        assert!(world.entry(p).unwrap().get_component::<Parent>().is_err());
+        assert_eq!(hierarchy::root(&world, b), p);
+    }
+
+    #[test]
+    fn reparents_orphans_to_grandparent() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+
+        let mut schedule = Schedule::builder()
+            .add_system(missing_previous_parent_system::build())
+            .flush()
+            .add_system(build_with_policy(HierarchyPolicy {
+                orphan: OrphanPolicy::ReparentToGrandparent,
+                ..Default::default()
+            }))
+            .build();
+
+        // The `LocalToWorld`s are set as if propagation had already run.
+        let grandparent = world.push((
+            Translation::new(1.0, 0.0, 0.0),
+            LocalToWorld(Translation::new(1.0, 0.0, 0.0).to_homogeneous()),
+        ));
+        let parent = world.push((
+            Translation::new(0.0, 2.0, 0.0),
+            LocalToParent::identity(),
+            LocalToWorld(Translation::new(1.0, 2.0, 0.0).to_homogeneous()),
+            Parent(grandparent),
+        ));
+        let child = world.push((
+            Translation::new(0.0, 0.0, 3.0),
+            LocalToParent::identity(),
+            LocalToWorld(Translation::new(1.0, 2.0, 3.0).to_homogeneous()),
+            Parent(parent),
+        ));
+
+        schedule.execute(&mut world, &mut resources);
+
+        // Delete `parent`.
+        world
+            .entry(parent)
+            .unwrap()
+            .remove_component::<LocalToWorld>();
+
+        schedule.execute(&mut world, &mut resources);
+
+        let entry = world.entry(child).unwrap();
+        assert_eq!(entry.get_component::<Parent>().unwrap().0, grandparent);
+        assert!(
+            (entry.get_component::<Translation>().unwrap().vector - Vector3::new(0.0, 2.0, 3.0))
+                .norm()
+                < 1e-5
+        );
+    }
+
+    #[test]
+    fn detaches_orphans_keeping_world() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+
+        let mut schedule = Schedule::builder()
+            .add_system(missing_previous_parent_system::build())
+            .flush()
+            .add_system(build_with_policy(HierarchyPolicy {
+                orphan: OrphanPolicy::DetachKeepWorld,
+                ..Default::default()
+            }))
+            .build();
+
+        // The `LocalToWorld`s are set as if propagation had already run. Each child starts with
+        // the kind of scale it doesn't end up with.
+        let parent = world.push((Translation::identity(), LocalToWorld::identity()));
+        let uniform = world.push((
+            NonUniformScale::new(1.0, 2.0, 3.0),
+            LocalToParent::identity(),
+            LocalToWorld(
+                Matrix4::new_translation(&Vector3::new(1.0, 2.0, 3.0)) * Matrix4::new_scaling(2.0),
+            ),
+            Parent(parent),
+        ));
+        let non_uniform = world.push((
+            Scale(2.0),
+            LocalToParent::identity(),
+            LocalToWorld(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                1.0, 2.0, 3.0,
+            ))),
+            Parent(parent),
+        ));
+
+        schedule.execute(&mut world, &mut resources);
+
+        // Delete `parent`.
+        world
+            .entry(parent)
+            .unwrap()
+            .remove_component::<LocalToWorld>();
+
+        schedule.execute(&mut world, &mut resources);
+
+        let entry = world.entry(uniform).unwrap();
+        assert!(entry.get_component::<Parent>().is_err());
+        assert!(entry.get_component::<LocalToParent>().is_err());
+        assert!(
+            (entry.get_component::<Translation>().unwrap().vector - Vector3::new(1.0, 2.0, 3.0))
+                .norm()
+                < 1e-5
+        );
+        assert!((entry.get_component::<Scale>().unwrap().0 - 2.0).abs() < 1e-5);
+        assert!(entry.get_component::<NonUniformScale>().is_err());
+
+        let entry = world.entry(non_uniform).unwrap();
+        assert!(entry.get_component::<Parent>().is_err());
+        assert!(
+            (entry.get_component::<NonUniformScale>().unwrap().0 - Vector3::new(1.0, 2.0, 3.0))
+                .norm()
+                < 1e-5
+        );
+        assert!(entry.get_component::<Scale>().is_err());
+    }
+
+    #[test]
+    fn deletes_orphans() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+
+        let mut schedule = Schedule::builder()
+            .add_system(missing_previous_parent_system::build())
+            .flush()
+            .add_system(build_with_policy(HierarchyPolicy {
+                orphan: OrphanPolicy::Delete,
+                ..Default::default()
+            }))
+            .build();
+
+        let parent = world.push((Translation::identity(), LocalToWorld::identity()));
+        let child = world.push((
+            Translation::identity(),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            Parent(parent),
+        ));
+        let grandchild = world.push((
+            Translation::identity(),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            Parent(child),
+        ));
+        let sibling = world.push((Translation::identity(), LocalToWorld::identity()));
+
+        schedule.execute(&mut world, &mut resources);
+
+        // Delete `parent`.
+        world
+            .entry(parent)
+            .unwrap()
+            .remove_component::<LocalToWorld>();
+
+        schedule.execute(&mut world, &mut resources);
+
+        // The whole subtree is gone, the rest of the world is left alone.
+        assert!(world.entry(child).is_none());
+        assert!(world.entry(grandchild).is_none());
+        assert!(world
+            .entry(parent)
+            .unwrap()
+            .get_component::<Children>()
+            .is_err());
+        assert!(world.entry(sibling).is_some());
+    }
+
+    #[test]
+    fn handles_deleted_entities() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+
+        // Entities are deleted outright below, so the cleanup runs first.
+        let mut schedule = Schedule::builder()
+            .add_system(build_cleanup(HierarchyPolicy::default()))
+            .flush()
+            .add_system(missing_previous_parent_system::build())
+            .flush()
+            .add_system(build())
+            .build();
+
+        let parent = world.push((Translation::identity(), LocalToWorld::identity()));
+        let children = world.extend(vec![
+            (
+                Translation::identity(),
+                LocalToParent::identity(),
+                LocalToWorld::identity(),
+                Parent(parent),
+            ),
+            (
+                Translation::identity(),
+                LocalToParent::identity(),
+                LocalToWorld::identity(),
+                Parent(parent),
+            ),
+        ]);
+        let (e1, e2) = (children[0], children[1]);
+        let grandchild = world.push((
+            Translation::identity(),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            Parent(e1),
+        ));
+
+        schedule.execute(&mut world, &mut resources);
+
+        // Deleting a child removes it from the parent's `Children`.
+        world.remove(e2);
+        schedule.execute(&mut world, &mut resources);
+        assert_eq!(
+            world
+                .entry(parent)
+                .unwrap()
+                .get_component::<Children>()
+                .unwrap()
+                .0
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![e1]
+        );
+
+        // Deleting a parent detaches its children (with the default `OrphanPolicy`).
+        world.remove(e1);
+        schedule.execute(&mut world, &mut resources);
+        let entry = world.entry(grandchild).unwrap();
+        assert!(entry.get_component::<Parent>().is_err());
+        assert!(entry.get_component::<PreviousParent>().is_err());
+        assert!(entry.get_component::<LocalToParent>().is_err());
+    }
 }
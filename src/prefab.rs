@@ -0,0 +1,235 @@
+//! Cloning hierarchies. A `Prefab` copies a subtree once, with Legion's world cloning, into a world
+//! of its own, and can then be instantiated any number of times, into the world it came from or
+//! any other. Every instance gets fresh entities, with `Parent`, `PreviousParent` and `Children`
+//! (as well as the entities referenced by `LookAt`, `Billboard` and constraints) pointing within
+//! the instance wherever they pointed within the subtree, so instances are independent of each
+//! other and of the original.
+//!
+//! Components are copied by a Legion `Merger`, normally a `Duplicate`, which only copies the
+//! component types registered with it. `register_clone_components` registers every component of
+//! this crate, the application's own components must be registered too.
+use crate::{
+    components::*,
+    ecs::{
+        world::{Duplicate, Entry, Merger},
+        *,
+    },
+    hierarchy,
+    snapshot::EntityReferences,
+};
+use std::collections::HashMap;
+
+/// Registers every component of this crate with `merger`, so that they are copied by `Prefab`
+/// and `clone_subtree`.
+pub fn register_clone_components(merger: &mut Duplicate) {
+    macro_rules! register {
+        ($($component:ident),*) => {
+            $(merger.register_clone::<$component>();)*
+        };
+    }
+    register!(
+        Billboard,
+        Children,
+        InterpolatedLocalToWorld,
+        LocalToParent,
+        LocalToParent2D,
+        LocalToWorld,
+        LocalToWorld2D,
+        LookAt,
+        Name,
+        NonUniformScale,
+        Parent,
+        ParentConstraint,
+        PositionConstraint,
+        PreviousLocalToWorld,
+        PreviousParent,
+        Rotation,
+        Rotation2D,
+        RotationConstraint,
+        Scale,
+        Scale2D,
+        ScaleConstraint,
+        Translation,
+        Translation2D,
+        WorldAngularVelocity,
+        WorldLinearVelocity,
+        WorldRotation,
+        WorldScale,
+        WorldToLocal,
+        WorldTranslation
+    );
+}
+
+pub struct Prefab {
+    world: World,
+    root: Entity,
+}
+
+impl Prefab {
+    /// Copies `root` and its descendants (found through `Children`), with every component
+    /// registered with `merger`. If `root` has a parent, it is left out, and instances are roots.
+    pub fn from_subtree<M: Merger>(world: &World, root: Entity, merger: &mut M) -> Self {
+        let mut prefab_world = World::default();
+        let map = clone_entities(&mut prefab_world, world, root, merger);
+        let prefab_root = map[&root];
+        if let Some(mut entry) = prefab_world.entry(prefab_root) {
+            entry.remove_component::<Parent>();
+            entry.remove_component::<PreviousParent>();
+            entry.remove_component::<LocalToParent>();
+            entry.remove_component::<LocalToParent2D>();
+        }
+        Self {
+            world: prefab_world,
+            root: prefab_root,
+        }
+    }
+
+    /// Spawns a copy of the subtree, returning the new root.
+    pub fn instantiate<M: Merger>(&self, world: &mut World, merger: &mut M) -> Entity {
+        self.instantiate_with(world, merger, |_| {})
+    }
+
+    /// Spawns a copy of the subtree, then lets `root` modify the components of the new root, eg.
+    /// to place the instance with a different `Translation`. Returns the new root.
+    pub fn instantiate_with<M: Merger, F: FnOnce(&mut Entry)>(
+        &self,
+        world: &mut World,
+        merger: &mut M,
+        root: F,
+    ) -> Entity {
+        let map = clone_entities(world, &self.world, self.root, merger);
+        let instance = map[&self.root];
+        if let Some(mut entry) = world.entry(instance) {
+            root(&mut entry);
+        }
+        instance
+    }
+}
+
+/// Spawns a copy of `root` and its descendants in the same world, returning the new root.
+pub fn clone_subtree<M: Merger>(world: &mut World, root: Entity, merger: &mut M) -> Entity {
+    Prefab::from_subtree(world, root, merger).instantiate(world, merger)
+}
+
+/// Clones `root` and its descendants from `source` into `world`, and remaps the entities they
+/// reference within the subtree. Returns the new entity of each cloned one.
+fn clone_entities<M: Merger>(
+    world: &mut World,
+    source: &World,
+    root: Entity,
+    merger: &mut M,
+) -> HashMap<Entity, Entity> {
+    let map = std::iter::once(root)
+        .chain(hierarchy::descendants_depth_first(source, root).map(|(entity, _)| entity))
+        .map(|entity| (entity, world.clone_from_single(source, entity, merger)))
+        .collect::<HashMap<_, _>>();
+
+    let remap = |entity: &mut Entity| {
+        if let Some(cloned) = map.get(entity) {
+            *entity = *cloned;
+        }
+    };
+    for cloned in map.values() {
+        let mut entry = match world.entry(*cloned) {
+            Some(entry) => entry,
+            None => continue,
+        };
+        if let Ok(parent) = entry.get_component_mut::<Parent>() {
+            remap(&mut parent.0);
+        }
+        if let Ok(PreviousParent(Some(previous_parent))) =
+            entry.get_component_mut::<PreviousParent>()
+        {
+            remap(previous_parent);
+        }
+        if let Ok(children) = entry.get_component_mut::<Children>() {
+            children.0.iter_mut().for_each(remap);
+        }
+        remap_references::<LookAt, _>(&mut entry, remap);
+        remap_references::<Billboard, _>(&mut entry, remap);
+        remap_references::<PositionConstraint, _>(&mut entry, remap);
+        remap_references::<RotationConstraint, _>(&mut entry, remap);
+        remap_references::<ScaleConstraint, _>(&mut entry, remap);
+        remap_references::<ParentConstraint, _>(&mut entry, remap);
+    }
+    map
+}
+
+fn remap_references<C: storage::Component + EntityReferences, F: Fn(&mut Entity)>(
+    entry: &mut Entry,
+    remap: F,
+) {
+    if let Ok(component) = entry.get_component_mut::<C>() {
+        component.entities_mut().into_iter().for_each(remap);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hierarchy_builder::*;
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    struct Health(u32);
+
+    #[test]
+    fn independent_instances() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut merger = Duplicate::default();
+        register_clone_components(&mut merger);
+        merger.register_copy::<Health>();
+
+        let mut world = World::default();
+        let camera = world.push((LocalToWorld::identity(),));
+        let rig = world.spawn_hierarchy((Translation::new(1.0, 0.0, 0.0), Health(10)), |parent| {
+            parent.with_children((Rotation::identity(),), |parent| {
+                parent.child((Scale(2.0), Billboard::spherical(camera)));
+            });
+        });
+        let arm = hierarchy::children(&world, rig)[0];
+        world
+            .entry(rig)
+            .unwrap()
+            .add_component(LookAt::new(LookAtTarget::Entity(arm)));
+
+        let prefab = Prefab::from_subtree(&world, rig, &mut merger);
+        let mut other_world = World::default();
+        let instance = prefab.instantiate_with(&mut other_world, &mut merger, |root| {
+            root.add_component(Translation::new(5.0, 0.0, 0.0));
+        });
+        let copy = clone_subtree(&mut world, rig, &mut merger);
+        assert_ne!(copy, rig);
+
+        for (world, root, translation) in &[
+            (&world, copy, Translation::new(1.0, 0.0, 0.0)),
+            (&other_world, instance, Translation::new(5.0, 0.0, 0.0)),
+        ] {
+            let entry = world.entry_ref(*root).unwrap();
+            assert_eq!(*entry.get_component::<Translation>().unwrap(), *translation);
+            // Components of the application are copied too.
+            assert_eq!(*entry.get_component::<Health>().unwrap(), Health(10));
+
+            let arm = hierarchy::children(*world, *root)[0];
+            let hand = hierarchy::children(*world, arm)[0];
+            assert_eq!(hierarchy::parent(*world, arm), Some(*root));
+            assert_eq!(hierarchy::parent(*world, hand), Some(arm));
+            assert!(![rig, copy, instance].contains(&arm));
+            // References within the subtree point within the copy, others are kept.
+            assert_eq!(
+                entry.get_component::<LookAt>().unwrap().target,
+                LookAtTarget::Entity(arm)
+            );
+            assert_eq!(
+                world
+                    .entry_ref(hand)
+                    .unwrap()
+                    .get_component::<Billboard>()
+                    .unwrap()
+                    .camera,
+                camera
+            );
+        }
+        assert_eq!(hierarchy::children(&world, rig).len(), 1);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{
+    components::*,
+    ecs::{systems::ParallelRunnable, *},
+};
+
+/// Snapshots `LocalToWorld` into `PreviousLocalToWorld`. This must run before any of the systems
+/// that write `LocalToWorld`, which is why it's the first system of the transform system bundle.
+/// The `floating_origin_system` shifts both components, so snapshots stay in the current space.
+pub fn build() -> impl ParallelRunnable {
+    SystemBuilder::<()>::new("PreviousLocalToWorldSystem")
+        // Only entities whose `LocalToWorld` changed during the last run need a new snapshot, the
+        // others already hold the correct previous value.
+        .with_query(
+            <(Write<PreviousLocalToWorld>, Read<LocalToWorld>)>::query()
+                .filter(maybe_changed::<LocalToWorld>()),
+        )
+        .build(move |_commands, world, _resource, query| {
+            query.for_each_mut(world, |(previous_local_to_world, local_to_world)| {
+                *previous_local_to_world = PreviousLocalToWorld(local_to_world.0);
+            });
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        floating_origin_system, local_to_parent_system, local_to_world_propagate_system,
+        local_to_world_system, missing_previous_parent_system, parent_update_system, resources::*,
+        world_velocity_system,
+    };
+
+    #[test]
+    fn snapshots_previous_frame() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+        let mut schedule = Schedule::builder()
+            .add_system(build())
+            .add_system(local_to_world_system::build())
+            .build();
+
+        let entity = world.push((
+            Translation::new(1.0, 0.0, 0.0),
+            LocalToWorld::identity(),
+            PreviousLocalToWorld::identity(),
+        ));
+
+        let get_previous = |world: &mut World| {
+            world
+                .entry(entity)
+                .unwrap()
+                .get_component::<PreviousLocalToWorld>()
+                .unwrap()
+                .0
+        };
+
+        // The first run snapshots the initial `LocalToWorld` before it is computed.
+        schedule.execute(&mut world, &mut resources);
+        assert_eq!(get_previous(&mut world), LocalToWorld::identity().0);
+
+        // Move the entity, the snapshot is now the first frame's `LocalToWorld`.
+        *world
+            .entry_mut(entity)
+            .unwrap()
+            .get_component_mut::<Translation>()
+            .unwrap() = Translation::new(2.0, 0.0, 0.0);
+        schedule.execute(&mut world, &mut resources);
+        assert_eq!(
+            get_previous(&mut world),
+            Translation::new(1.0, 0.0, 0.0).to_homogeneous()
+        );
+
+        // Nothing moved, the snapshot catches up to the current `LocalToWorld`.
+        schedule.execute(&mut world, &mut resources);
+        assert_eq!(
+            get_previous(&mut world),
+            Translation::new(2.0, 0.0, 0.0).to_homogeneous()
+        );
+    }
+
+    #[test]
+    fn origin_shifts_are_not_movement() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        resources.insert(TransformTimeStep(1.0));
+        let mut world = World::default();
+        let mut schedule = Schedule::builder()
+            .add_system(floating_origin_system::build())
+            .add_system(build())
+            .add_system(missing_previous_parent_system::build())
+            .flush()
+            .add_system(parent_update_system::build())
+            .flush()
+            .add_system(local_to_parent_system::build())
+            .add_system(local_to_world_system::build())
+            .add_system(local_to_world_propagate_system::build())
+            .add_system(world_velocity_system::build())
+            .build();
+
+        let focus = world.push((
+            Translation::new(200.0, 0.0, 0.0),
+            LocalToWorld::identity(),
+            PreviousLocalToWorld::identity(),
+            WorldLinearVelocity::default(),
+        ));
+        let child = world.push((
+            Translation::new(0.0, 0.0, 1.0),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            PreviousLocalToWorld::identity(),
+            WorldLinearVelocity::default(),
+            Parent(focus),
+        ));
+        resources.insert(FloatingOrigin::new(focus, 100.0));
+        resources.insert(OriginShiftEvents::default());
+
+        // The first run places everything, the second shifts the origin.
+        schedule.execute(&mut world, &mut resources);
+        schedule.execute(&mut world, &mut resources);
+        assert_eq!(resources.get::<OriginShiftEvents>().unwrap().0.len(), 1);
+
+        // Nothing actually moved.
+        for entity in &[focus, child] {
+            let velocity = world
+                .entry(*entity)
+                .unwrap()
+                .get_component::<WorldLinearVelocity>()
+                .unwrap()
+                .0;
+            assert!(velocity.norm() < 1e-3);
+        }
+    }
+}
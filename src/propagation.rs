@@ -0,0 +1,230 @@
+//! The hierarchy traversal shared by the 3D and 2D propagate systems, which only differ in the
+//! matrices they multiply.
+use crate::{
+    components::*,
+    ecs::{systems::ParallelRunnable, world::SubWorld, *},
+    hierarchy,
+};
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+/// Entities with at least this many children have each child's subtree propagated in parallel.
+pub(crate) const PARALLEL_CHILDREN_THRESHOLD: usize = 64;
+
+/// A world-space transform, computed from the parent's and the entity's `LocalToParent`.
+pub(crate) trait WorldTransform: storage::Component + Copy {
+    type LocalToParent: storage::Component + Copy;
+
+    /// The type names, for log messages.
+    const NAME: &'static str;
+    const LOCAL_TO_PARENT_NAME: &'static str;
+
+    fn child(&self, local_to_parent: &Self::LocalToParent) -> Self;
+}
+
+impl WorldTransform for LocalToWorld {
+    type LocalToParent = LocalToParent;
+
+    const NAME: &'static str = "LocalToWorld";
+    const LOCAL_TO_PARENT_NAME: &'static str = "LocalToParent";
+
+    fn child(&self, local_to_parent: &LocalToParent) -> Self {
+        LocalToWorld(self.0 * local_to_parent.0)
+    }
+}
+
+impl WorldTransform for LocalToWorld2D {
+    type LocalToParent = LocalToParent2D;
+
+    const NAME: &'static str = "LocalToWorld2D";
+    const LOCAL_TO_PARENT_NAME: &'static str = "LocalToParent2D";
+
+    fn child(&self, local_to_parent: &LocalToParent2D) -> Self {
+        LocalToWorld2D(self.0 * local_to_parent.0)
+    }
+}
+
+/// The entities whose world transform must be re-computed (and all their descendants), along with
+/// the ancestors of those entities which must be walked through to reach them.
+struct DirtySet {
+    changed: HashSet<Entity>,
+    on_path: HashSet<Entity>,
+}
+
+pub(crate) fn build<L: WorldTransform>(name: &'static str) -> impl ParallelRunnable {
+    SystemBuilder::<()>::new(name)
+        // Entities with a `Children` and world transform but NOT a `Parent` (ie those that are
+        // roots of a hierarchy).
+        .with_query(<(Entity, Read<Children>, Read<L>)>::query().filter(!component::<Parent>()))
+        // Roots with a changed world transform.
+        .with_query(
+            <(Entity, Read<L>)>::query()
+                .filter(!component::<Parent>() & component::<Children>() & maybe_changed::<L>()),
+        )
+        // Children with a changed `LocalToParent` or `Parent`.
+        .with_query(<(Entity, Read<L::LocalToParent>)>::query().filter(
+            component::<Parent>()
+                & (maybe_changed::<L::LocalToParent>() | maybe_changed::<Parent>()),
+        ))
+        .read_component::<Children>()
+        .read_component::<Parent>()
+        .read_component::<L::LocalToParent>()
+        .write_component::<L>()
+        .build(move |_commands, world, _resource, queries| {
+            let mut dirty = DirtySet {
+                changed: queries.1.iter(world).map(|(entity, _)| *entity).collect(),
+                on_path: HashSet::new(),
+            };
+            let changed_children = queries
+                .2
+                .iter(world)
+                .map(|(entity, _)| *entity)
+                .collect::<Vec<_>>();
+            for entity in changed_children {
+                if !dirty.changed.insert(entity) {
+                    continue;
+                }
+                // Ancestors already on the path had theirs added too, so stop at the first one.
+                for ancestor in hierarchy::ancestors(world, entity) {
+                    if !dirty.on_path.insert(ancestor) {
+                        break;
+                    }
+                }
+            }
+
+            if dirty.changed.is_empty() {
+                return;
+            }
+
+            // Only roots that changed, or that have a changed descendant, need to be visited.
+            let roots = queries
+                .0
+                .iter(world)
+                .filter(|(entity, _, _)| {
+                    dirty.changed.contains(*entity) || dirty.on_path.contains(*entity)
+                })
+                .map(|(entity, children, local_to_world)| {
+                    (*entity, children.0.clone(), *local_to_world)
+                })
+                .collect::<Vec<_>>();
+
+            // Each root is propagated in parallel, collecting the new world transform of every
+            // descendant. Collecting preserves the order of the roots, so the updates are always
+            // written in the same order.
+            let updates = roots
+                .par_iter()
+                .map(|(root, children, local_to_world)| {
+                    let root_changed = dirty.changed.contains(root);
+                    let mut updates = Vec::new();
+                    for child in children.iter() {
+                        propagate_recursive(
+                            local_to_world,
+                            root_changed,
+                            world,
+                            &dirty,
+                            *child,
+                            &mut updates,
+                        );
+                    }
+                    updates
+                })
+                .collect::<Vec<_>>();
+
+            // Write the results directly, so they are visible to the rest of the frame without
+            // needing a command buffer flush.
+            for (entity, local_to_world) in updates.into_iter().flatten() {
+                if let Some(target) = world
+                    .entry_mut(entity)
+                    .and_then(|entry| entry.into_component_mut::<L>().ok())
+                {
+                    *target = local_to_world;
+                } else {
+                    log::warn!(
+                        "Entity {:?} is a child in the hierarchy but does not have a {}",
+                        entity,
+                        L::NAME
+                    );
+                }
+            }
+        })
+}
+
+fn propagate_recursive<L: WorldTransform>(
+    parent_local_to_world: &L,
+    parent_changed: bool,
+    world: &SubWorld,
+    dirty: &DirtySet,
+    entity: Entity,
+    updates: &mut Vec<(Entity, L)>,
+) {
+    // Branches without any change are skipped entirely.
+    let changed = parent_changed || dirty.changed.contains(&entity);
+    if !changed && !dirty.on_path.contains(&entity) {
+        return;
+    }
+
+    let entry = if let Some(entry) = world.entry_ref(entity) {
+        entry
+    } else {
+        log::warn!(
+            "Entity {:?} is a child in the hierarchy but does not exist",
+            entity
+        );
+        return;
+    };
+
+    let new_local_to_world = if changed {
+        log::trace!("Updating {} for {:?}", L::NAME, entity);
+        let local_to_parent = if let Ok(local_to_parent) = entry.get_component::<L::LocalToParent>()
+        {
+            *local_to_parent
+        } else {
+            log::warn!(
+                "Entity {:?} is a child in the hierarchy but does not have a {}",
+                entity,
+                L::LOCAL_TO_PARENT_NAME
+            );
+            return;
+        };
+
+        let new_local_to_world = parent_local_to_world.child(&local_to_parent);
+        updates.push((entity, new_local_to_world));
+        new_local_to_world
+    } else {
+        // Only passing through to reach a changed descendant, the current value is still correct.
+        match entry.get_component::<L>() {
+            Ok(local_to_world) => *local_to_world,
+            Err(_) => return,
+        }
+    };
+
+    // Collect children
+    let children = entry
+        .get_component::<Children>()
+        .map(|e| e.0.iter().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    if children.len() >= PARALLEL_CHILDREN_THRESHOLD {
+        // Large subtrees are split up by child, again preserving the order of the results.
+        let child_updates = children
+            .par_iter()
+            .map(|child| {
+                let mut child_updates = Vec::new();
+                propagate_recursive(
+                    &new_local_to_world,
+                    changed,
+                    world,
+                    dirty,
+                    *child,
+                    &mut child_updates,
+                );
+                child_updates
+            })
+            .collect::<Vec<_>>();
+        updates.extend(child_updates.into_iter().flatten());
+    } else {
+        for child in children {
+            propagate_recursive(&new_local_to_world, changed, world, dirty, child, updates);
+        }
+    }
+}
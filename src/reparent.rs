@@ -0,0 +1,362 @@
+//! Changing the `Parent` of an entity without moving it. Setting `Parent` directly keeps the
+//! entity's `Translation`, `Rotation` and `Scale`, which are then interpreted in the new parent's
+//! space, so the entity jumps. These functions instead re-compute the local transform from the
+//! current `LocalToWorld` of the entity and of its new parent (ie. as of the last propagation).
+//!
+//! Also has functions to control the order of `Children`, which the transform system bundle only
+//! ever appends to (in `Entity` order, when several children are added in the same run).
+use crate::{
+    components::*,
+    decompose::{decompose, is_uniform, shear},
+    ecs::*,
+    hierarchy,
+    math::Matrix4,
+    Float,
+};
+use smallvec::SmallVec;
+use std::{error::Error, fmt};
+
+/// The largest `shear` (cosine between two axes) accepted in a new local transform.
+const SHEAR_TOLERANCE: Float = 1.0e-4;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ReparentError {
+    /// The entity (or the new parent) has no `LocalToWorld`.
+    MissingLocalToWorld(Entity),
+    /// The new parent's `LocalToWorld` cannot be inverted (ie. has a zero scale).
+    NonInvertibleParent(Entity),
+    /// The new parent is the entity itself, or one of its descendants.
+    Cycle,
+    /// The entity (or the new parent) is not in the world.
+    MissingEntity(Entity),
+    /// Keeping the world transform would need a local transform with this much shear (see
+    /// `SHEAR_TOLERANCE`), which `Translation`, `Rotation` and `NonUniformScale` cannot represent.
+    /// This happens when a rotated child is moved under a non-uniformly scaled parent.
+    Shear(Float),
+}
+
+impl fmt::Display for ReparentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReparentError::MissingLocalToWorld(entity) => {
+                write!(f, "{:?} has no LocalToWorld", entity)
+            }
+            ReparentError::NonInvertibleParent(entity) => {
+                write!(
+                    f,
+                    "the LocalToWorld of the new parent {:?} is not invertible",
+                    entity
+                )
+            }
+            ReparentError::Cycle => write!(f, "an entity cannot be parented to its own subtree"),
+            ReparentError::MissingEntity(entity) => write!(f, "{:?} is not in the world", entity),
+            ReparentError::Shear(shear) => write!(
+                f,
+                "keeping the world transform would need a sheared local transform ({})",
+                shear
+            ),
+        }
+    }
+}
+
+impl Error for ReparentError {}
+
+/// Parents `entity` to `parent`, writing its `Translation`, `Rotation` and `Scale` (or
+/// `NonUniformScale`) so that its world transform is unchanged. Adds a `LocalToParent` if needed.
+/// Nothing is changed if an error is returned.
+pub fn set_parent_keep_world(
+    world: &mut World,
+    entity: Entity,
+    parent: Entity,
+) -> Result<(), ReparentError> {
+    if parent == entity || hierarchy::ancestors(world, parent).any(|a| a == entity) {
+        return Err(ReparentError::Cycle);
+    }
+
+    let world_to_parent = local_to_world(world, parent)?
+        .try_inverse()
+        .ok_or(ReparentError::NonInvertibleParent(parent))?;
+    let local_to_parent = world_to_parent * local_to_world(world, entity)?;
+    set_local_transform(world, entity, &local_to_parent)?;
+
+    let mut entry = world.entry(entity).unwrap();
+    entry.add_component(Parent(parent));
+    if entry.get_component::<LocalToParent>().is_err() {
+        entry.add_component(LocalToParent::identity());
+    }
+    Ok(())
+}
+
+/// Removes the `Parent` (and `LocalToParent`) of `entity`, making it a root, and writes its
+/// `Translation`, `Rotation` and `Scale` (or `NonUniformScale`) so that its world transform is
+/// unchanged. Nothing is changed if an error is returned.
+pub fn detach_keep_world(world: &mut World, entity: Entity) -> Result<(), ReparentError> {
+    let local_to_world = local_to_world(world, entity)?;
+    set_local_transform(world, entity, &local_to_world)?;
+
+    let mut entry = world.entry(entity).unwrap();
+    entry.remove_component::<Parent>();
+    entry.remove_component::<LocalToParent>();
+    Ok(())
+}
+
+/// Parents `child` to `parent`, inserting it at `index` in the parent's `Children` (or last, if
+/// `index` is past the end), and removes it from the `Children` of its previous parent. Unlike
+/// setting `Parent`, `Children` is updated right away. The local transform of `child` is kept as
+/// is; use `set_parent_keep_world` first to keep its world transform instead. Adds a
+/// `LocalToParent` if needed. Nothing is changed if an error is returned.
+pub fn insert_child(
+    world: &mut World,
+    parent: Entity,
+    index: usize,
+    child: Entity,
+) -> Result<(), ReparentError> {
+    for entity in &[parent, child] {
+        if world.entry_ref(*entity).is_none() {
+            return Err(ReparentError::MissingEntity(*entity));
+        }
+    }
+    if parent == child || hierarchy::ancestors(world, parent).any(|a| a == child) {
+        return Err(ReparentError::Cycle);
+    }
+
+    let previous_parent = world
+        .entry_ref(child)
+        .and_then(|entry| entry.into_component::<PreviousParent>().ok())
+        .and_then(|previous_parent| previous_parent.0);
+    let previous_parents: SmallVec<[Entity; 2]> = hierarchy::parent(world, child)
+        .into_iter()
+        .chain(previous_parent)
+        .collect();
+    for previous_parent in previous_parents {
+        if let Some(children) = children_mut(world, previous_parent) {
+            children.0.retain(|e| *e != child);
+        }
+    }
+
+    if let Some(children) = children_mut(world, parent) {
+        let index = index.min(children.0.len());
+        children.0.insert(index, child);
+    } else {
+        world
+            .entry(parent)
+            .unwrap()
+            .add_component(Children::with(&[child]));
+    }
+
+    let mut entry = world.entry(child).unwrap();
+    entry.add_component(Parent(parent));
+    // Already in the `Children` of `parent`, so the bundle has nothing left to do.
+    entry.add_component(PreviousParent(Some(parent)));
+    if entry.get_component::<LocalToParent>().is_err() {
+        entry.add_component(LocalToParent::identity());
+    }
+    Ok(())
+}
+
+/// The position of `entity` in the `Children` of its parent.
+pub fn sibling_index<W: EntityStore>(world: &W, entity: Entity) -> Option<usize> {
+    hierarchy::parent(world, entity).and_then(|parent| {
+        hierarchy::children(world, parent)
+            .iter()
+            .position(|e| *e == entity)
+    })
+}
+
+/// Swaps `entity` with its previous sibling. Returns false if it is already first (or has no
+/// parent).
+pub fn move_child_up(world: &mut World, entity: Entity) -> bool {
+    match sibling_index(world, entity) {
+        Some(index) if index > 0 => swap_siblings(world, entity, index - 1, index),
+        _ => false,
+    }
+}
+
+/// Swaps `entity` with its next sibling. Returns false if it is already last (or has no parent).
+pub fn move_child_down(world: &mut World, entity: Entity) -> bool {
+    match sibling_index(world, entity) {
+        Some(index) => swap_siblings(world, entity, index, index + 1),
+        None => false,
+    }
+}
+
+fn swap_siblings(world: &mut World, entity: Entity, a: usize, b: usize) -> bool {
+    let parent = match hierarchy::parent(world, entity) {
+        Some(parent) => parent,
+        None => return false,
+    };
+    match children_mut(world, parent) {
+        Some(children) if b < children.0.len() => {
+            children.0.swap(a, b);
+            true
+        }
+        _ => false,
+    }
+}
+
+fn children_mut(world: &mut World, entity: Entity) -> Option<&mut Children> {
+    world
+        .entry_mut(entity)
+        .and_then(|entry| entry.into_component_mut::<Children>().ok())
+}
+
+fn local_to_world(world: &World, entity: Entity) -> Result<Matrix4<Float>, ReparentError> {
+    world
+        .entry_ref(entity)
+        .and_then(|entry| entry.into_component::<LocalToWorld>().ok())
+        .map(|local_to_world| local_to_world.0)
+        .ok_or(ReparentError::MissingLocalToWorld(entity))
+}
+
+fn set_local_transform(
+    world: &mut World,
+    entity: Entity,
+    matrix: &Matrix4<Float>,
+) -> Result<(), ReparentError> {
+    let shear = shear(matrix);
+    if shear > SHEAR_TOLERANCE {
+        return Err(ReparentError::Shear(shear));
+    }
+
+    let (translation, rotation, scale) = decompose(matrix);
+    let mut entry = world.entry(entity).unwrap();
+    entry.add_component(Translation::from(translation));
+    entry.add_component(Rotation::from(rotation));
+    if is_uniform(&scale) {
+        entry.remove_component::<NonUniformScale>();
+        entry.add_component(Scale(scale.x));
+    } else {
+        entry.remove_component::<Scale>();
+        entry.add_component(NonUniformScale::from(scale));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::Vector3;
+
+    #[test]
+    fn keeps_world_transform() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut world = World::default();
+
+        // The `LocalToWorld`s are set as if propagation had already run.
+        let hand = world.push((
+            Translation::new(1.0, 0.0, 0.0),
+            Scale(2.0),
+            LocalToWorld(
+                Matrix4::new_translation(&Vector3::new(1.0, 0.0, 0.0)).prepend_scaling(2.0),
+            ),
+        ));
+        let item = world.push((
+            Translation::new(1.0, 2.0, 3.0),
+            LocalToWorld(Matrix4::new_translation(&Vector3::new(1.0, 2.0, 3.0))),
+        ));
+
+        set_parent_keep_world(&mut world, item, hand).unwrap();
+        {
+            let entry = world.entry(item).unwrap();
+            assert_eq!(entry.get_component::<Parent>().unwrap().0, hand);
+            assert!(
+                (entry.get_component::<Translation>().unwrap().vector
+                    - Vector3::new(0.0, 1.0, 1.5))
+                .norm()
+                    < 1e-5
+            );
+            assert!((entry.get_component::<Scale>().unwrap().0 - 0.5).abs() < 1e-5);
+        }
+        assert_eq!(
+            set_parent_keep_world(&mut world, hand, item),
+            Err(ReparentError::Cycle)
+        );
+
+        detach_keep_world(&mut world, item).unwrap();
+        let entry = world.entry(item).unwrap();
+        assert!(entry.get_component::<Parent>().is_err());
+        assert!(
+            (entry.get_component::<Translation>().unwrap().vector - Vector3::new(1.0, 2.0, 3.0))
+                .norm()
+                < 1e-5
+        );
+
+        // A rotated child can't keep its shape under a non-uniformly scaled parent.
+        let stretched = world.push((LocalToWorld(Matrix4::new_nonuniform_scaling(
+            &Vector3::new(1.0, 2.0, 1.0),
+        )),));
+        world.entry(item).unwrap().add_component(LocalToWorld(
+            Rotation::from_euler_angles(0.0, 0.0, 0.7).to_homogeneous(),
+        ));
+        match set_parent_keep_world(&mut world, item, stretched) {
+            Err(ReparentError::Shear(_)) => {}
+            result => panic!("Expected a shear error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn orders_children() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut world = World::default();
+
+        let parent = world.push((LocalToWorld::identity(),));
+        let other_parent = world.push((LocalToWorld::identity(),));
+        let children = world.extend(vec![
+            (LocalToWorld::identity(), LocalToParent::identity());
+            3
+        ]);
+        let (a, b, c) = (children[0], children[1], children[2]);
+        let children_of = |world: &World| hierarchy::children(world, parent).to_vec();
+
+        insert_child(&mut world, parent, 0, a).unwrap();
+        insert_child(&mut world, parent, 0, b).unwrap();
+        insert_child(&mut world, parent, 1, c).unwrap();
+        assert_eq!(children_of(&world), vec![b, c, a]);
+        assert_eq!(sibling_index(&world, a), Some(2));
+
+        assert!(move_child_up(&mut world, a));
+        assert_eq!(children_of(&world), vec![b, a, c]);
+        assert!(!move_child_up(&mut world, b));
+        assert!(move_child_down(&mut world, b));
+        assert_eq!(children_of(&world), vec![a, b, c]);
+        assert!(!move_child_down(&mut world, c));
+
+        // Moving to another parent removes it from the previous `Children`.
+        insert_child(&mut world, other_parent, 5, b).unwrap();
+        assert_eq!(children_of(&world), vec![a, c]);
+        assert_eq!(hierarchy::children(&world, other_parent).to_vec(), vec![b]);
+        assert_eq!(
+            insert_child(&mut world, b, 0, other_parent),
+            Err(ReparentError::Cycle)
+        );
+    }
+
+    #[test]
+    fn inserts_roots() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut world = World::default();
+
+        let parent = world.push((LocalToWorld::identity(),));
+        let root = world.push((LocalToWorld::identity(), Translation::new(1.0, 0.0, 0.0)));
+
+        insert_child(&mut world, parent, 0, root).unwrap();
+        {
+            let entry = world.entry(root).unwrap();
+            assert_eq!(entry.get_component::<Parent>().unwrap().0, parent);
+            assert!(entry.get_component::<LocalToParent>().is_ok());
+        }
+        assert_eq!(hierarchy::children(&world, parent).to_vec(), vec![root]);
+
+        // A deleted parent is refused, leaving the child where it was.
+        let deleted = world.push((LocalToWorld::identity(),));
+        world.remove(deleted);
+        assert_eq!(
+            insert_child(&mut world, deleted, 0, root),
+            Err(ReparentError::MissingEntity(deleted))
+        );
+        assert_eq!(hierarchy::parent(&world, root), Some(parent));
+    }
+}
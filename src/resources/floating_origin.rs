@@ -0,0 +1,46 @@
+use crate::{ecs::*, math::Vector3, Float};
+use shrinkwraprs::Shrinkwrap;
+
+/// Resource driving the `floating_origin_system`. `origin` is the accumulated world-space offset
+/// of the current origin, ie. the 'true' position of something is its `LocalToWorld` translation
+/// plus `origin`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct FloatingOrigin {
+    /// The accumulated offset of every shift made so far.
+    pub origin: Vector3<Float>,
+    /// The entity (normally the camera or player) that must stay close to the origin.
+    pub focus: Option<Entity>,
+    /// How far the focus may stray from the origin before the world is shifted.
+    pub threshold: Float,
+}
+
+impl FloatingOrigin {
+    pub fn new(focus: Entity, threshold: Float) -> Self {
+        Self {
+            origin: Vector3::zeros(),
+            focus: Some(focus),
+            threshold,
+        }
+    }
+}
+
+impl Default for FloatingOrigin {
+    fn default() -> Self {
+        Self {
+            origin: Vector3::zeros(),
+            focus: None,
+            threshold: 1000.0,
+        }
+    }
+}
+
+/// Emitted each time the world is shifted. Every root has been moved by `-offset`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct OriginShifted {
+    pub offset: Vector3<Float>,
+}
+
+/// The `OriginShifted` events emitted by the last run of the `floating_origin_system`.
+#[derive(Shrinkwrap, Debug, Default, Clone)]
+#[shrinkwrap(mutable)]
+pub struct OriginShiftEvents(pub Vec<OriginShifted>);
@@ -0,0 +1,20 @@
+use crate::ecs::*;
+use shrinkwraprs::Shrinkwrap;
+
+/// Problems found (and repaired) in the hierarchy by the `parent_update_system`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum HierarchyEvent {
+    /// Setting the `Parent` of `entity` to `parent` would have formed a cycle. `cycle` lists the
+    /// entities of the cycle, starting with `entity` and following `Parent`.
+    CycleDetected {
+        entity: Entity,
+        parent: Entity,
+        cycle: Vec<Entity>,
+    },
+}
+
+/// The `HierarchyEvent`s emitted by the last run of a `parent_update_system` built with
+/// `build_with_events`.
+#[derive(Shrinkwrap, Debug, Default, Clone)]
+#[shrinkwrap(mutable)]
+pub struct HierarchyEvents(pub Vec<HierarchyEvent>);
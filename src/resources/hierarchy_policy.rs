@@ -0,0 +1,46 @@
+/// Configures how the `parent_update_system` repairs an invalid hierarchy.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct HierarchyPolicy {
+    pub cycle: CyclePolicy,
+    pub orphan: OrphanPolicy,
+}
+
+/// What to do with a `Parent` that would make an entity its own ancestor.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CyclePolicy {
+    /// Refuse the change by restoring the `Parent` to the `PreviousParent` (or removing it if
+    /// there was none).
+    Refuse,
+    /// Break the link by removing the `Parent`, making the entity a root.
+    Break,
+}
+
+impl Default for CyclePolicy {
+    fn default() -> Self {
+        CyclePolicy::Refuse
+    }
+}
+
+/// What to do with the children of a deleted parent (ie. one that lost its `LocalToWorld`).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OrphanPolicy {
+    /// Remove `Parent`, `PreviousParent` and `LocalToParent` from the children, making them roots
+    /// placed by their local transform alone (so they jump, unless the parent was at the origin).
+    Detach,
+    /// Like `Detach`, but bake each child's current `LocalToWorld` into its `Translation`,
+    /// `Rotation` and `Scale` (or `NonUniformScale`) so it stays where it was.
+    DetachKeepWorld,
+    /// Move the children to the deleted parent's own `Parent`, re-computing their local transform
+    /// so they stay where they were. Children of a deleted root are detached as with
+    /// `DetachKeepWorld`, and so are the children of a parent removed from the world outright
+    /// (rather than only losing its `LocalToWorld`), whose own parent can't be known anymore.
+    ReparentToGrandparent,
+    /// Delete the children, along with all of their descendants.
+    Delete,
+}
+
+impl Default for OrphanPolicy {
+    fn default() -> Self {
+        OrphanPolicy::Detach
+    }
+}
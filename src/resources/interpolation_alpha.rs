@@ -0,0 +1,14 @@
+use crate::Float;
+use shrinkwraprs::Shrinkwrap;
+
+/// How far (from `0.0` to `1.0`) rendering is between the previous and the current fixed
+/// simulation step. `0.0` renders `PreviousLocalToWorld` and `1.0` renders `LocalToWorld`.
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy)]
+#[shrinkwrap(mutable)]
+pub struct InterpolationAlpha(pub Float);
+
+impl Default for InterpolationAlpha {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
@@ -0,0 +1,11 @@
+mod floating_origin;
+mod hierarchy_events;
+mod hierarchy_policy;
+mod interpolation_alpha;
+mod time_step;
+
+pub use floating_origin::*;
+pub use hierarchy_events::*;
+pub use hierarchy_policy::*;
+pub use interpolation_alpha::*;
+pub use time_step::*;
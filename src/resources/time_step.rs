@@ -0,0 +1,14 @@
+use crate::Float;
+use shrinkwraprs::Shrinkwrap;
+
+/// The time (in seconds) between two runs of the transform system bundle, used to derive
+/// velocities from `PreviousLocalToWorld`.
+#[derive(Shrinkwrap, Debug, PartialEq, Clone, Copy)]
+#[shrinkwrap(mutable)]
+pub struct TransformTimeStep(pub Float);
+
+impl Default for TransformTimeStep {
+    fn default() -> Self {
+        Self(1.0 / 60.0)
+    }
+}
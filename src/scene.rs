@@ -0,0 +1,342 @@
+//! A human readable (RON) scene format for authoring transform hierarchies by hand:
+//!
+//! ```ron
+//! #![enable(implicit_some)]
+//! (
+//!     nodes: [
+//!         (
+//!             name: "turret",
+//!             translation: (1.0, 2.0, 3.0),
+//!             children: [
+//!                 (name: "barrel", rotation: Euler(0.0, 0.5, 0.0), scale: 2.0),
+//!                 (rotation: Quaternion(0.0, 0.0, 0.0, 1.0), non_uniform_scale: (1.0, 2.0, 1.0)),
+//!             ],
+//!         ),
+//!     ],
+//! )
+//! ```
+//!
+//! Every field of a node is optional. Loading gives each node a `LocalToWorld`, and each child a
+//! `Parent`, `PreviousParent` and `LocalToParent`, with `Children` filled in.
+use crate::{
+    components::*,
+    ecs::*,
+    hierarchy,
+    math::{Quaternion, UnitQuaternion, Vector3},
+    Float,
+};
+use ron::{extensions::Extensions, ser::PrettyConfig};
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt, fs, io, path::Path};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SceneRotation {
+    /// Roll, pitch and yaw in radians, as in `Rotation::from_euler_angles`.
+    Euler(Float, Float, Float),
+    /// The `x`, `y`, `z` and `w` components of a quaternion, which is normalized on load (so it
+    /// can't be zero).
+    Quaternion(Float, Float, Float, Float),
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct SceneNode {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translation: Option<(Float, Float, Float)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<SceneRotation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale: Option<Float>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub non_uniform_scale: Option<(Float, Float, Float)>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<SceneNode>,
+}
+
+/// A list of root nodes, each with its own hierarchy of children.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct Scene {
+    pub nodes: Vec<SceneNode>,
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    Ron(ron::Error),
+    /// A `Quaternion` rotation that can't be normalized (ie. zero, or not finite), along with the
+    /// name of its node, if any.
+    InvalidQuaternion(Option<String>),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(error) => write!(f, "failed to access the scene file: {}", error),
+            SceneError::Ron(error) => write!(f, "invalid scene: {}", error),
+            SceneError::InvalidQuaternion(Some(name)) => write!(
+                f,
+                "invalid scene: the rotation of {:?} is not a valid quaternion",
+                name
+            ),
+            SceneError::InvalidQuaternion(None) => write!(
+                f,
+                "invalid scene: the rotation of an unnamed node is not a valid quaternion"
+            ),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Io(error) => Some(error),
+            SceneError::Ron(error) => Some(error),
+            SceneError::InvalidQuaternion(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for SceneError {
+    fn from(error: io::Error) -> Self {
+        SceneError::Io(error)
+    }
+}
+
+impl From<ron::Error> for SceneError {
+    fn from(error: ron::Error) -> Self {
+        SceneError::Ron(error)
+    }
+}
+
+impl Scene {
+    /// Parses a scene, rejecting quaternions that can't be normalized into a rotation.
+    pub fn from_ron(text: &str) -> Result<Self, SceneError> {
+        let scene: Self = ron::from_str(text)?;
+        validate_nodes(&scene.nodes)?;
+        Ok(scene)
+    }
+
+    /// Pretty-prints the scene. Rotations are always written as quaternions, so they round-trip
+    /// exactly.
+    pub fn to_ron(&self) -> Result<String, SceneError> {
+        let config = PrettyConfig::new()
+            .with_decimal_floats(true)
+            .with_extensions(Extensions::IMPLICIT_SOME);
+        Ok(ron::ser::to_string_pretty(self, config)?)
+    }
+
+    /// Spawns an entity for every node, returning the roots.
+    pub fn spawn(&self, world: &mut World) -> Vec<Entity> {
+        self.nodes
+            .iter()
+            .map(|node| spawn_node(world, node, None))
+            .collect()
+    }
+
+    /// Captures `roots` and all of their descendants (found through `Children`) as a scene.
+    pub fn capture<W: EntityStore>(world: &W, roots: &[Entity]) -> Self {
+        Self {
+            nodes: roots
+                .iter()
+                .map(|root| capture_node(world, *root))
+                .collect(),
+        }
+    }
+}
+
+/// Reads a RON scene from `path` and spawns it into `world`, returning the root entities.
+pub fn load_scene<P: AsRef<Path>>(world: &mut World, path: P) -> Result<Vec<Entity>, SceneError> {
+    let scene = Scene::from_ron(&fs::read_to_string(path)?)?;
+    Ok(scene.spawn(world))
+}
+
+/// Writes `roots` and their descendants to `path` as a RON scene.
+pub fn save_scene<W: EntityStore, P: AsRef<Path>>(
+    world: &W,
+    roots: &[Entity],
+    path: P,
+) -> Result<(), SceneError> {
+    fs::write(path, Scene::capture(world, roots).to_ron()?)?;
+    Ok(())
+}
+
+fn validate_nodes(nodes: &[SceneNode]) -> Result<(), SceneError> {
+    for node in nodes {
+        if let Some(SceneRotation::Quaternion(x, y, z, w)) = node.rotation {
+            let norm = Quaternion::new(w, x, y, z).norm();
+            if !norm.is_finite() || norm < 1.0e-6 {
+                return Err(SceneError::InvalidQuaternion(node.name.clone()));
+            }
+        }
+        validate_nodes(&node.children)?;
+    }
+    Ok(())
+}
+
+fn spawn_node(world: &mut World, node: &SceneNode, parent: Option<Entity>) -> Entity {
+    let entity = world.push((LocalToWorld::identity(),));
+    let mut entry = world.entry(entity).unwrap();
+    if let Some(name) = &node.name {
+        entry.add_component(Name(name.clone()));
+    }
+    if let Some((x, y, z)) = node.translation {
+        entry.add_component(Translation::new(x, y, z));
+    }
+    match node.rotation {
+        Some(SceneRotation::Euler(roll, pitch, yaw)) => {
+            entry.add_component(Rotation::from_euler_angles(roll, pitch, yaw))
+        }
+        Some(SceneRotation::Quaternion(x, y, z, w)) => {
+            let quaternion = Quaternion::new(w, x, y, z);
+            // Re-normalizing an already normalized quaternion can change it slightly, which
+            // would break exact round-trips.
+            entry.add_component(Rotation(if (quaternion.norm() - 1.0).abs() < 1.0e-6 {
+                UnitQuaternion::new_unchecked(quaternion)
+            } else {
+                UnitQuaternion::from_quaternion(quaternion)
+            }))
+        }
+        None => {}
+    }
+    if let Some(scale) = node.scale {
+        entry.add_component(Scale(scale));
+    }
+    if let Some((x, y, z)) = node.non_uniform_scale {
+        entry.add_component(NonUniformScale::new(x, y, z));
+    }
+    if let Some(parent) = parent {
+        entry.add_component(Parent(parent));
+        entry.add_component(PreviousParent(Some(parent)));
+        entry.add_component(LocalToParent::identity());
+    }
+
+    let children = node
+        .children
+        .iter()
+        .map(|child| spawn_node(world, child, Some(entity)))
+        .collect::<Vec<_>>();
+    if !children.is_empty() {
+        world
+            .entry(entity)
+            .unwrap()
+            .add_component(Children::with(&children));
+    }
+    entity
+}
+
+fn capture_node<W: EntityStore>(world: &W, entity: Entity) -> SceneNode {
+    let entry = match world.entry_ref(entity) {
+        Some(entry) => entry,
+        None => return SceneNode::default(),
+    };
+    let tuple = |vector: &Vector3<Float>| (vector.x, vector.y, vector.z);
+    SceneNode {
+        name: entry
+            .get_component::<Name>()
+            .ok()
+            .map(|name| name.0.clone()),
+        translation: entry
+            .get_component::<Translation>()
+            .ok()
+            .map(|translation| tuple(&translation.vector)),
+        rotation: entry.get_component::<Rotation>().ok().map(|rotation| {
+            let quaternion = rotation.0.into_inner();
+            SceneRotation::Quaternion(quaternion.i, quaternion.j, quaternion.k, quaternion.w)
+        }),
+        scale: entry.get_component::<Scale>().ok().map(|scale| scale.0),
+        non_uniform_scale: entry
+            .get_component::<NonUniformScale>()
+            .ok()
+            .map(|scale| tuple(&scale.0)),
+        children: hierarchy::children(world, entity)
+            .into_iter()
+            .map(|child| capture_node(world, child))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SCENE: &str = r#"
+#![enable(implicit_some)]
+(
+    nodes: [
+        (
+            name: "turret",
+            translation: (1.0, 2.0, 3.0),
+            children: [
+                (name: "barrel", rotation: Euler(0.0, 0.5, 0.0), scale: 2.0),
+                (rotation: Quaternion(0.0, 0.0, 0.0, 2.0), non_uniform_scale: (1.0, 2.0, 1.0)),
+            ],
+        ),
+        (name: "empty"),
+    ],
+)
+"#;
+
+    #[test]
+    fn round_trip() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut world = World::default();
+        let roots = Scene::from_ron(SCENE).unwrap().spawn(&mut world);
+        assert_eq!(roots.len(), 2);
+
+        let turret = roots[0];
+        let children = hierarchy::children(&world, turret);
+        assert_eq!(children.len(), 2);
+        let barrel = world.entry(children[0]).unwrap();
+        assert_eq!(barrel.get_component::<Name>().unwrap().0, "barrel");
+        assert_eq!(barrel.get_component::<Parent>().unwrap().0, turret);
+        assert_eq!(barrel.get_component::<Scale>().unwrap().0, 2.0);
+        assert!(barrel.get_component::<LocalToParent>().is_ok());
+        assert_eq!(
+            world
+                .entry(children[1])
+                .unwrap()
+                .get_component::<Rotation>()
+                .unwrap()
+                .0,
+            UnitQuaternion::identity()
+        );
+
+        // Save to and re-load from a file.
+        let path = std::env::temp_dir().join("legion_transform_scene_round_trip.ron");
+        save_scene(&world, &roots, &path).unwrap();
+        let mut other_world = World::default();
+        let other_roots = load_scene(&mut other_world, &path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            Scene::capture(&other_world, &other_roots),
+            Scene::capture(&world, &roots)
+        );
+    }
+
+    #[test]
+    fn rejects_zero_quaternions() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let scene = r#"
+#![enable(implicit_some)]
+(
+    nodes: [
+        (
+            children: [
+                (name: "barrel", rotation: Quaternion(0.0, 0.0, 0.0, 0.0)),
+            ],
+        ),
+    ],
+)
+"#;
+        match Scene::from_ron(scene) {
+            Err(SceneError::InvalidQuaternion(name)) => assert_eq!(name.as_deref(), Some("barrel")),
+            result => panic!("expected an invalid quaternion error, got {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,418 @@
+//! Saving and loading transform hierarchies. Every component derives `Serialize` and
+//! `Deserialize`, and `register_components` adds them all to a legion serialization `Registry`.
+//!
+//! `Parent`, `PreviousParent` and `Children` hold raw `Entity` values though, which mean nothing
+//! in another world (or another run). A `TransformSnapshot` stores parents as indices into its
+//! own list of entities instead, and `load` remaps them to freshly created entities. The same goes
+//! for the entities referenced by `LookAt`, `Billboard` and the constraints: references within the
+//! snapshot are stored as indices, references to other entities are kept as they are (and are
+//! only meaningful when loading back into the same world).
+use crate::{
+    components::*,
+    ecs::{
+        serialize::Registry,
+        world::{Entry, EntryRef},
+        *,
+    },
+    hierarchy,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Registers every component of this crate with a legion serialization `Registry`, keyed by its
+/// type name (eg. `"legion_transform::Translation"`).
+pub fn register_components(registry: &mut Registry<String>) {
+    macro_rules! register {
+        ($($component:ident),*) => {
+            $(registry.register::<$component>(
+                concat!("legion_transform::", stringify!($component)).to_string(),
+            );)*
+        };
+    }
+    register!(
+        Billboard,
+        Children,
+        InterpolatedLocalToWorld,
+        LocalToParent,
+        LocalToParent2D,
+        LocalToWorld,
+        LocalToWorld2D,
+        LookAt,
+        Name,
+        NonUniformScale,
+        Parent,
+        ParentConstraint,
+        PositionConstraint,
+        PreviousLocalToWorld,
+        PreviousParent,
+        Rotation,
+        Rotation2D,
+        RotationConstraint,
+        Scale,
+        Scale2D,
+        ScaleConstraint,
+        Translation,
+        Translation2D,
+        WorldAngularVelocity,
+        WorldLinearVelocity,
+        WorldRotation,
+        WorldScale,
+        WorldToLocal,
+        WorldTranslation
+    );
+}
+
+/// Components holding references to other entities.
+pub trait EntityReferences {
+    fn entities_mut(&mut self) -> Vec<&mut Entity>;
+}
+
+impl EntityReferences for LookAt {
+    fn entities_mut(&mut self) -> Vec<&mut Entity> {
+        match &mut self.target {
+            LookAtTarget::Entity(target) => vec![target],
+            LookAtTarget::Point(_) => Vec::new(),
+        }
+    }
+}
+
+impl EntityReferences for Billboard {
+    fn entities_mut(&mut self) -> Vec<&mut Entity> {
+        vec![&mut self.camera]
+    }
+}
+
+macro_rules! constraint_references {
+    ($($constraint:ty),*) => {
+        $(
+            impl EntityReferences for $constraint {
+                fn entities_mut(&mut self) -> Vec<&mut Entity> {
+                    self.sources.iter_mut().map(|source| &mut source.entity).collect()
+                }
+            }
+        )*
+    };
+}
+
+constraint_references!(
+    PositionConstraint,
+    RotationConstraint,
+    ScaleConstraint,
+    ParentConstraint
+);
+
+/// A component along with the snapshot indices of the entities it references.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Referencing<C> {
+    pub component: C,
+    /// In `EntityReferences::entities_mut` order, `None` for entities outside the snapshot.
+    pub indices: Vec<Option<usize>>,
+}
+
+impl<C: EntityReferences + Clone> Referencing<C> {
+    fn capture(component: &C, indices: &HashMap<Entity, usize>) -> Self {
+        let mut component = component.clone();
+        let indices = component
+            .entities_mut()
+            .into_iter()
+            .map(|entity| indices.get(entity).cloned())
+            .collect();
+        Self { component, indices }
+    }
+
+    fn restore(&self, entities: &[Entity]) -> C {
+        let mut component = self.component.clone();
+        for (entity, index) in component
+            .entities_mut()
+            .into_iter()
+            .zip(self.indices.iter())
+        {
+            if let Some(restored) = index.and_then(|index| entities.get(index)) {
+                *entity = *restored;
+            }
+        }
+        component
+    }
+}
+
+macro_rules! entity_snapshot {
+    (
+        $($field:ident: $component:ty),*;
+        references: $($reference_field:ident: $reference_component:ty),*
+    ) => {
+        /// The transform components of a single entity. Hierarchy components are not stored, they
+        /// are re-created from `parent` on load.
+        #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+        pub struct EntitySnapshot {
+            /// The index of the parent in `TransformSnapshot::entities`.
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub parent: Option<usize>,
+            $(
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+                pub $field: Option<$component>,
+            )*
+            $(
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+                pub $reference_field: Option<Referencing<$reference_component>>,
+            )*
+        }
+
+        impl EntitySnapshot {
+            /// `indices` only needs to hold the entities referenced by `entry`.
+            fn capture(entry: &EntryRef, indices: &HashMap<Entity, usize>) -> Self {
+                Self {
+                    parent: None,
+                    $($field: entry.get_component::<$component>().ok().cloned(),)*
+                    $(
+                        $reference_field: entry
+                            .get_component::<$reference_component>()
+                            .ok()
+                            .map(|component| Referencing::capture(component, indices)),
+                    )*
+                }
+            }
+
+            /// `entities` are the new entities of the snapshot, in order.
+            fn restore(&self, entry: &mut Entry, entities: &[Entity]) {
+                $(
+                    if let Some(component) = &self.$field {
+                        entry.add_component(component.clone());
+                    }
+                )*
+                $(
+                    if let Some(component) = &self.$reference_field {
+                        entry.add_component(component.restore(entities));
+                    }
+                )*
+            }
+        }
+    };
+}
+
+entity_snapshot!(
+    name: Name,
+    translation: Translation,
+    rotation: Rotation,
+    scale: Scale,
+    non_uniform_scale: NonUniformScale,
+    local_to_parent: LocalToParent,
+    local_to_world: LocalToWorld,
+    world_to_local: WorldToLocal,
+    previous_local_to_world: PreviousLocalToWorld,
+    interpolated_local_to_world: InterpolatedLocalToWorld,
+    world_linear_velocity: WorldLinearVelocity,
+    world_angular_velocity: WorldAngularVelocity,
+    world_translation: WorldTranslation,
+    world_rotation: WorldRotation,
+    world_scale: WorldScale,
+    translation_2d: Translation2D,
+    rotation_2d: Rotation2D,
+    scale_2d: Scale2D,
+    local_to_parent_2d: LocalToParent2D,
+    local_to_world_2d: LocalToWorld2D;
+    references:
+    look_at: LookAt,
+    billboard: Billboard,
+    position_constraint: PositionConstraint,
+    rotation_constraint: RotationConstraint,
+    scale_constraint: ScaleConstraint,
+    parent_constraint: ParentConstraint
+);
+
+/// A serializable copy of one or more transform hierarchies.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct TransformSnapshot {
+    /// Parents always come before their children, and siblings are in `Children` order.
+    pub entities: Vec<EntitySnapshot>,
+}
+
+impl TransformSnapshot {
+    /// Captures `root` and all of its descendants. `Children` must be up to date, ie. the
+    /// transform system bundle must have run since the hierarchy last changed.
+    pub fn save_subtree<W: EntityStore>(world: &W, root: Entity) -> Self {
+        Self::capture(world, &[root])
+    }
+
+    /// Captures every hierarchy in `world`, ie. all entities with a `LocalToWorld` or
+    /// `LocalToWorld2D` and no `Parent`, and their descendants.
+    pub fn save_world<W: EntityStore>(world: &W) -> Self {
+        let roots = Entity::query()
+            .filter(
+                !component::<Parent>()
+                    & (component::<LocalToWorld>() | component::<LocalToWorld2D>()),
+            )
+            .iter(world)
+            .cloned()
+            .collect::<Vec<_>>();
+        Self::capture(world, &roots)
+    }
+
+    /// Creates an entity for each entity of the snapshot, with `Parent`, `PreviousParent` and
+    /// `Children` (and references within the snapshot) pointing at the new entities. Returns the
+    /// new entities, in snapshot order.
+    pub fn load(&self, world: &mut World) -> Vec<Entity> {
+        // Legion can't push an entity without components, so start with a `LocalToWorld` and
+        // drop it again if the snapshot doesn't have one.
+        let entities = self
+            .entities
+            .iter()
+            .map(|_| world.push((LocalToWorld::identity(),)))
+            .collect::<Vec<_>>();
+
+        let mut children = HashMap::<Entity, Vec<Entity>>::new();
+        for (snapshot, entity) in self.entities.iter().zip(entities.iter()) {
+            let mut entry = world.entry(*entity).unwrap();
+            if snapshot.local_to_world.is_none() {
+                entry.remove_component::<LocalToWorld>();
+            }
+            snapshot.restore(&mut entry, &entities);
+
+            let parent = match snapshot.parent.and_then(|index| entities.get(index)) {
+                Some(parent) => *parent,
+                None => continue,
+            };
+            entry.add_component(Parent(parent));
+            entry.add_component(PreviousParent(Some(parent)));
+            children.entry(parent).or_default().push(*entity);
+        }
+        for (parent, children) in children {
+            world
+                .entry(parent)
+                .unwrap()
+                .add_component(Children::with(&children));
+        }
+
+        entities
+    }
+
+    fn capture<W: EntityStore>(world: &W, roots: &[Entity]) -> Self {
+        let subtrees = roots
+            .iter()
+            .flat_map(|root| {
+                std::iter::once(*root).chain(
+                    hierarchy::descendants_depth_first(world, *root).map(|(entity, _)| entity),
+                )
+            })
+            .filter(|entity| world.entry_ref(*entity).is_some())
+            .collect::<Vec<_>>();
+        // Every index is known up front, as entities can reference ones that come after them.
+        let indices = subtrees
+            .iter()
+            .enumerate()
+            .map(|(index, entity)| (*entity, index))
+            .collect::<HashMap<_, _>>();
+
+        let entities = subtrees
+            .iter()
+            .map(|entity| {
+                let mut snapshot =
+                    EntitySnapshot::capture(&world.entry_ref(*entity).unwrap(), &indices);
+                // The roots' parents (if any) aren't part of the snapshot.
+                snapshot.parent = hierarchy::parent(world, *entity)
+                    .and_then(|parent| indices.get(&parent))
+                    .cloned();
+                snapshot
+            })
+            .collect();
+        Self { entities }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hierarchy_builder::*;
+
+    #[test]
+    fn round_trip() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut world = World::default();
+        let root = world.spawn_hierarchy((Translation::new(1.0, 0.0, 0.0),), |parent| {
+            parent
+                .with_children((Rotation::from_euler_angles(0.0, 1.0, 0.0),), |parent| {
+                    parent.child((Scale(2.0),));
+                })
+                .child((NonUniformScale::new(1.0, 2.0, 3.0),));
+        });
+        let snapshot = TransformSnapshot::save_subtree(&world, root);
+        assert_eq!(snapshot.entities.len(), 4);
+
+        let text = ron::to_string(&snapshot).unwrap();
+        let loaded = ron::from_str::<TransformSnapshot>(&text).unwrap();
+        assert_eq!(loaded, snapshot);
+
+        let mut other_world = World::default();
+        let entities = loaded.load(&mut other_world);
+        assert_eq!(TransformSnapshot::save_world(&other_world), snapshot);
+
+        let (root, a, c, b) = (entities[0], entities[1], entities[2], entities[3]);
+        assert_eq!(hierarchy::children(&other_world, root).to_vec(), vec![a, b]);
+        assert_eq!(hierarchy::children(&other_world, a).to_vec(), vec![c]);
+        assert_eq!(
+            other_world
+                .entry(c)
+                .unwrap()
+                .get_component::<PreviousParent>()
+                .unwrap()
+                .0,
+            Some(a)
+        );
+        assert_eq!(
+            *other_world
+                .entry(b)
+                .unwrap()
+                .get_component::<NonUniformScale>()
+                .unwrap(),
+            NonUniformScale::new(1.0, 2.0, 3.0)
+        );
+    }
+
+    #[test]
+    fn remaps_references() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut world = World::default();
+        let camera = world.push((LocalToWorld::identity(),));
+        let root = world.spawn_hierarchy((Translation::new(1.0, 0.0, 0.0),), |parent| {
+            parent.child((Billboard::spherical(camera),));
+        });
+        let child = hierarchy::children(&world, root)[0];
+        world
+            .entry(root)
+            .unwrap()
+            .add_component(LookAt::new(LookAtTarget::Entity(child)));
+
+        let snapshot = TransformSnapshot::save_subtree(&world, root);
+        assert_eq!(
+            snapshot.entities[0].look_at.as_ref().unwrap().indices,
+            vec![Some(1)]
+        );
+        assert_eq!(
+            snapshot.entities[1].billboard.as_ref().unwrap().indices,
+            vec![None]
+        );
+
+        let entities = snapshot.load(&mut world);
+        let (root, child) = (entities[0], entities[1]);
+        assert_eq!(
+            world
+                .entry(root)
+                .unwrap()
+                .get_component::<LookAt>()
+                .unwrap()
+                .target,
+            LookAtTarget::Entity(child)
+        );
+        // The camera isn't part of the snapshot, so it is kept as is.
+        assert_eq!(
+            world
+                .entry(child)
+                .unwrap()
+                .get_component::<Billboard>()
+                .unwrap()
+                .camera,
+            camera
+        );
+    }
+}
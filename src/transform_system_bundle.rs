@@ -1,15 +1,55 @@
 use crate::{
-    ecs::systems::ParallelRunnable, local_to_parent_system, local_to_world_propagate_system,
-    local_to_world_system, missing_previous_parent_system, parent_update_system,
+    billboard_system, constraint_system, ecs::systems::ParallelRunnable, local_to_parent_system,
+    local_to_parent_system_2d, local_to_world_propagate_system, local_to_world_propagate_system_2d,
+    local_to_world_system, local_to_world_system_2d, look_at_system,
+    missing_previous_parent_system, parent_update_system, previous_local_to_world_system,
+    world_to_local_system, world_transform_system,
 };
 
 pub fn build() -> Vec<Box<dyn ParallelRunnable>> {
-    let mut all_systems = Vec::<Box<dyn ParallelRunnable>>::with_capacity(5);
+    build_with_parent_update(parent_update_system::build())
+}
+
+/// Like `build`, with `parent_update` maintaining the hierarchy instead of the default
+/// `parent_update_system::build()`, eg. one with a different `HierarchyPolicy`, or reporting
+/// `HierarchyEvents`.
+pub fn build_with_parent_update<S: ParallelRunnable + 'static>(
+    parent_update: S,
+) -> Vec<Box<dyn ParallelRunnable>> {
+    let mut all_systems = Vec::<Box<dyn ParallelRunnable>>::with_capacity(12);
+    all_systems.push(Box::new(previous_local_to_world_system::build()));
     all_systems.push(Box::new(missing_previous_parent_system::build()));
-    all_systems.push(Box::new(parent_update_system::build()));
+    all_systems.push(Box::new(parent_update));
+    all_systems.push(Box::new(look_at_system::build()));
     all_systems.push(Box::new(local_to_parent_system::build()));
     all_systems.push(Box::new(local_to_world_system::build()));
     all_systems.push(Box::new(local_to_world_propagate_system::build()));
+    all_systems.push(Box::new(constraint_system::build()));
+    all_systems.push(Box::new(constraint_system::build_propagate()));
+    all_systems.push(Box::new(billboard_system::build()));
+    all_systems.push(Box::new(world_to_local_system::build()));
+    all_systems.push(Box::new(world_transform_system::build()));
+
+    all_systems
+}
+
+/// The 2D counterpart of `build`. Entities use `Translation2D`, `Rotation2D` and `Scale2D` along
+/// with `LocalToParent2D` and `LocalToWorld2D`, and share the `Parent` / `Children` hierarchy
+/// maintenance with the 3D systems.
+pub fn build_2d() -> Vec<Box<dyn ParallelRunnable>> {
+    build_2d_with_parent_update(parent_update_system::build())
+}
+
+/// The 2D counterpart of `build_with_parent_update`.
+pub fn build_2d_with_parent_update<S: ParallelRunnable + 'static>(
+    parent_update: S,
+) -> Vec<Box<dyn ParallelRunnable>> {
+    let mut all_systems = Vec::<Box<dyn ParallelRunnable>>::with_capacity(5);
+    all_systems.push(Box::new(missing_previous_parent_system::build()));
+    all_systems.push(Box::new(parent_update));
+    all_systems.push(Box::new(local_to_parent_system_2d::build()));
+    all_systems.push(Box::new(local_to_world_system_2d::build()));
+    all_systems.push(Box::new(local_to_world_propagate_system_2d::build()));
 
     all_systems
 }
@@ -0,0 +1,133 @@
+//! Placing an entity in world space, wherever it is in a hierarchy. The setters work out the local
+//! `Translation` / `Rotation` from the `LocalToWorld` of the entity's parent (as of the last
+//! propagation), so that the entity ends up at the requested world transform once the transform
+//! system bundle has run.
+use crate::{
+    components::*,
+    decompose::decompose,
+    ecs::*,
+    hierarchy,
+    math::{Matrix4, Point3, UnitQuaternion, Vector3},
+    Float,
+};
+use std::{error::Error, fmt};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum WorldSpaceError {
+    /// The parent of the entity has no `LocalToWorld`.
+    MissingParentLocalToWorld(Entity),
+    /// The parent's `LocalToWorld` cannot be inverted (ie. has a zero scale).
+    NonInvertibleParent(Entity),
+}
+
+impl fmt::Display for WorldSpaceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WorldSpaceError::MissingParentLocalToWorld(parent) => {
+                write!(f, "the parent {:?} has no LocalToWorld", parent)
+            }
+            WorldSpaceError::NonInvertibleParent(parent) => {
+                write!(
+                    f,
+                    "the LocalToWorld of the parent {:?} is not invertible",
+                    parent
+                )
+            }
+        }
+    }
+}
+
+impl Error for WorldSpaceError {}
+
+/// Sets the `Translation` of `entity` so that its world-space position becomes `translation`. This
+/// is exact whatever the rotation and scale of the parents.
+pub fn set_world_translation(
+    world: &mut World,
+    entity: Entity,
+    translation: Vector3<Float>,
+) -> Result<(), WorldSpaceError> {
+    let world_to_parent = world_to_parent(world, entity)?;
+    let local = world_to_parent.transform_point(&Point3::from(translation));
+    if let Some(mut entry) = world.entry(entity) {
+        entry.add_component(Translation::from(local.coords));
+    }
+    Ok(())
+}
+
+/// Sets the `Rotation` of `entity` so that its world-space rotation becomes `rotation`. This is
+/// exact when the parents have uniform (positive) scales; under a non-uniform scale the world
+/// rotation of the entity is sheared, and only approximates `rotation`.
+pub fn set_world_rotation(
+    world: &mut World,
+    entity: Entity,
+    rotation: UnitQuaternion<Float>,
+) -> Result<(), WorldSpaceError> {
+    let parent_rotation = match hierarchy::parent(world, entity) {
+        Some(parent) => decompose(&parent_local_to_world(world, parent)?).1,
+        None => UnitQuaternion::identity(),
+    };
+    if let Some(mut entry) = world.entry(entity) {
+        entry.add_component(Rotation(parent_rotation.inverse() * rotation));
+    }
+    Ok(())
+}
+
+fn world_to_parent(world: &World, entity: Entity) -> Result<Matrix4<Float>, WorldSpaceError> {
+    match hierarchy::parent(world, entity) {
+        Some(parent) => parent_local_to_world(world, parent)?
+            .try_inverse()
+            .ok_or(WorldSpaceError::NonInvertibleParent(parent)),
+        None => Ok(Matrix4::identity()),
+    }
+}
+
+fn parent_local_to_world(world: &World, parent: Entity) -> Result<Matrix4<Float>, WorldSpaceError> {
+    world
+        .entry_ref(parent)
+        .and_then(|entry| entry.into_component::<LocalToWorld>().ok())
+        .map(|local_to_world| local_to_world.0)
+        .ok_or(WorldSpaceError::MissingParentLocalToWorld(parent))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn lands_in_world_space() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut world = World::default();
+
+        // The `LocalToWorld` is set as if propagation had already run.
+        let parent_local_to_world = UnitQuaternion::from_euler_angles(0.0, 0.0, FRAC_PI_2 as Float)
+            .to_homogeneous()
+            .append_translation(&Vector3::new(1.0, 0.0, 0.0))
+            .prepend_scaling(2.0);
+        let parent = world.push((LocalToWorld(parent_local_to_world),));
+        let child = world.push((
+            Translation::identity(),
+            Rotation::identity(),
+            LocalToParent::identity(),
+            LocalToWorld::identity(),
+            Parent(parent),
+        ));
+
+        let target_translation = Vector3::new(1.0, 2.0, 3.0);
+        let target_rotation = UnitQuaternion::from_euler_angles(0.3, 0.0, 0.0);
+        set_world_translation(&mut world, child, target_translation).unwrap();
+        set_world_rotation(&mut world, child, target_rotation).unwrap();
+
+        // What `local_to_parent_system` and propagation will compute.
+        let entry = world.entry(child).unwrap();
+        let local_to_parent = entry
+            .get_component::<Translation>()
+            .unwrap()
+            .to_homogeneous()
+            * entry.get_component::<Rotation>().unwrap().to_homogeneous();
+        let (translation, rotation, _) = decompose(&(parent_local_to_world * local_to_parent));
+        assert!((translation - target_translation).norm() < 1e-5);
+        assert!(rotation.angle_to(&target_rotation) < 1e-5);
+    }
+}
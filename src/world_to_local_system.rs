@@ -0,0 +1,70 @@
+use crate::{
+    components::*,
+    ecs::{systems::ParallelRunnable, *},
+    math::Matrix4,
+};
+
+pub fn build() -> impl ParallelRunnable {
+    SystemBuilder::<()>::new("WorldToLocalUpdateSystem")
+        // Entities that opted-in to a `WorldToLocal` and whose `LocalToWorld` changed.
+        .with_query(
+            <(Entity, Write<WorldToLocal>, Read<LocalToWorld>)>::query()
+                .filter(maybe_changed::<LocalToWorld>()),
+        )
+        .build(move |_commands, world, _resource, query| {
+            query.for_each_mut(world, |(entity, world_to_local, local_to_world)| {
+                *world_to_local = match local_to_world.try_inverse() {
+                    Some(inverse) => WorldToLocal(inverse),
+                    None => {
+                        log::warn!(
+                            "Entity {:?} has a LocalToWorld that cannot be inverted.",
+                            entity
+                        );
+                        WorldToLocal(Matrix4::zeros())
+                    }
+                };
+            });
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn correct_inverse() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+        let mut schedule = Schedule::builder().add_system(build()).build();
+
+        let ltw = LocalToWorld(
+            Translation::new(1.0, 2.0, 3.0)
+                .to_homogeneous()
+                .prepend_scaling(2.0),
+        );
+        let invertible = world.push((ltw, WorldToLocal::identity()));
+        let zero_scale = world.push((
+            LocalToWorld(Matrix4::new_scaling(0.0)),
+            WorldToLocal::identity(),
+        ));
+
+        schedule.execute(&mut world, &mut resources);
+
+        let world_to_local = *world
+            .entry(invertible)
+            .unwrap()
+            .get_component::<WorldToLocal>()
+            .unwrap();
+        assert!(world_to_local.is_invertible());
+        assert!((world_to_local.0 * ltw.0 - Matrix4::identity()).norm() < 1e-5);
+
+        assert!(!world
+            .entry(zero_scale)
+            .unwrap()
+            .get_component::<WorldToLocal>()
+            .unwrap()
+            .is_invertible());
+    }
+}
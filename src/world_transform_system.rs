@@ -0,0 +1,106 @@
+use crate::{
+    components::*,
+    decompose::decompose,
+    ecs::{systems::ParallelRunnable, *},
+    math::Vector3,
+};
+
+/// Decomposes `LocalToWorld` into the opt-in `WorldTranslation`, `WorldRotation` and `WorldScale`
+/// components, whenever it changed. Part of the transform system bundle, after propagation.
+pub fn build() -> impl ParallelRunnable {
+    SystemBuilder::<()>::new("WorldTransformSystem")
+        .with_query(
+            <(Write<WorldTranslation>, Read<LocalToWorld>)>::query()
+                .filter(maybe_changed::<LocalToWorld>()),
+        )
+        .with_query(
+            <(Write<WorldRotation>, Read<LocalToWorld>)>::query()
+                .filter(maybe_changed::<LocalToWorld>()),
+        )
+        .with_query(
+            <(Write<WorldScale>, Read<LocalToWorld>)>::query()
+                .filter(maybe_changed::<LocalToWorld>()),
+        )
+        .build(move |_commands, world, _resource, queries| {
+            queries
+                .0
+                .for_each_mut(world, |(world_translation, local_to_world)| {
+                    // The translation is just the last column, no need to decompose.
+                    *world_translation = WorldTranslation(Vector3::new(
+                        local_to_world[(0, 3)],
+                        local_to_world[(1, 3)],
+                        local_to_world[(2, 3)],
+                    ));
+                });
+            queries
+                .1
+                .for_each_mut(world, |(world_rotation, local_to_world)| {
+                    *world_rotation = WorldRotation(decompose(&local_to_world.0).1);
+                });
+            queries
+                .2
+                .for_each_mut(world, |(world_scale, local_to_world)| {
+                    *world_scale = WorldScale(decompose(&local_to_world.0).2);
+                });
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        math::{Matrix4, UnitQuaternion},
+        Float,
+    };
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn correct_world_transform() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+        let mut schedule = Schedule::builder().add_system(build()).build();
+
+        let rotation = UnitQuaternion::from_euler_angles(0.0, 0.0, FRAC_PI_2 as Float);
+        let entity = world.push((
+            LocalToWorld(
+                rotation
+                    .to_homogeneous()
+                    .append_translation(&Vector3::new(1.0, 2.0, 3.0))
+                    .prepend_nonuniform_scaling(&Vector3::new(-1.0, 2.0, 3.0)),
+            ),
+            WorldTranslation::default(),
+            WorldRotation::default(),
+            WorldScale::default(),
+        ));
+
+        schedule.execute(&mut world, &mut resources);
+
+        let entry = world.entry(entity).unwrap();
+        assert!(
+            (entry.get_component::<WorldTranslation>().unwrap().0 - Vector3::new(1.0, 2.0, 3.0))
+                .norm()
+                < 1e-5
+        );
+        assert!(
+            entry
+                .get_component::<WorldRotation>()
+                .unwrap()
+                .angle_to(&rotation)
+                < 1e-5
+        );
+        assert!(
+            (entry.get_component::<WorldScale>().unwrap().0 - Vector3::new(-1.0, 2.0, 3.0)).norm()
+                < 1e-5
+        );
+
+        // Sheared matrices report how far off the decomposition is.
+        let sheared = LocalToWorld(
+            Matrix4::new_nonuniform_scaling(&Vector3::new(1.0, 2.0, 1.0))
+                * UnitQuaternion::from_euler_angles(0.0, 0.0, 0.7).to_homogeneous(),
+        );
+        assert!(LocalToWorld::identity().decompose().shear < 1e-5);
+        assert!(sheared.decompose().shear > 0.1);
+    }
+}
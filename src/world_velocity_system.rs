@@ -0,0 +1,95 @@
+use crate::{
+    components::*,
+    decompose::decompose,
+    ecs::{systems::ParallelRunnable, *},
+    math::Vector3,
+    resources::*,
+};
+
+/// Derives `WorldLinearVelocity` and `WorldAngularVelocity` from the change between
+/// `PreviousLocalToWorld` and `LocalToWorld` over the `TransformTimeStep` resource. Requires the
+/// `TransformTimeStep` resource and should be scheduled after the transform system bundle.
+pub fn build() -> impl ParallelRunnable {
+    SystemBuilder::<()>::new("WorldVelocitySystem")
+        .read_resource::<TransformTimeStep>()
+        // Linear velocity
+        .with_query(
+            <(
+                Write<WorldLinearVelocity>,
+                Read<PreviousLocalToWorld>,
+                Read<LocalToWorld>,
+            )>::query()
+            .filter(maybe_changed::<PreviousLocalToWorld>() | maybe_changed::<LocalToWorld>()),
+        )
+        // Angular velocity
+        .with_query(
+            <(
+                Write<WorldAngularVelocity>,
+                Read<PreviousLocalToWorld>,
+                Read<LocalToWorld>,
+            )>::query()
+            .filter(maybe_changed::<PreviousLocalToWorld>() | maybe_changed::<LocalToWorld>()),
+        )
+        .build(move |_commands, world, time_step, queries| {
+            let time_step = time_step.0;
+            if time_step <= 0.0 {
+                log::warn!("TransformTimeStep must be positive to derive velocities.");
+                return;
+            }
+
+            queries.0.for_each_mut(
+                world,
+                |(linear_velocity, previous_local_to_world, local_to_world)| {
+                    let (previous_translation, _, _) = decompose(&previous_local_to_world.0);
+                    let (translation, _, _) = decompose(&local_to_world.0);
+                    *linear_velocity =
+                        WorldLinearVelocity((translation - previous_translation) / time_step);
+                },
+            );
+
+            queries.1.for_each_mut(
+                world,
+                |(angular_velocity, previous_local_to_world, local_to_world)| {
+                    let (_, previous_rotation, _) = decompose(&previous_local_to_world.0);
+                    let (_, rotation, _) = decompose(&local_to_world.0);
+                    let delta: Vector3<_> = (rotation * previous_rotation.inverse()).scaled_axis();
+                    *angular_velocity = WorldAngularVelocity(delta / time_step);
+                },
+            );
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn correct_velocities() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut resources = Resources::default();
+        let mut world = World::default();
+        let mut schedule = Schedule::builder().add_system(build()).build();
+
+        resources.insert(TransformTimeStep(0.5));
+
+        let entity = world.push((
+            PreviousLocalToWorld::identity(),
+            LocalToWorld(
+                Rotation::from_euler_angles(0.0, 0.0, 0.5)
+                    .to_homogeneous()
+                    .append_translation(&Vector3::new(1.0, 0.0, 0.0)),
+            ),
+            WorldLinearVelocity::default(),
+            WorldAngularVelocity::default(),
+        ));
+
+        schedule.execute(&mut world, &mut resources);
+
+        let entry = world.entry(entity).unwrap();
+        let linear = entry.get_component::<WorldLinearVelocity>().unwrap().0;
+        let angular = entry.get_component::<WorldAngularVelocity>().unwrap().0;
+        assert!((linear - Vector3::new(2.0, 0.0, 0.0)).norm() < 1e-5);
+        assert!((angular - Vector3::new(0.0, 0.0, 1.0)).norm() < 1e-5);
+    }
+}